@@ -0,0 +1,319 @@
+use crate::{vars, PathEnv};
+use std::{fmt, path::Path, slice};
+
+/// How much an [`AuditIssue`] exposes programs that search `PATH`.
+///
+/// Severities are ordered, so `severity >= Severity::Medium` selects the
+/// more serious findings.
+///
+/// [`AuditIssue`]: enum.AuditIssue.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A risk that depends on who else is trusted, such as a group.
+    Low,
+    /// A risk that another user can exploit under some conditions.
+    Medium,
+    /// A risk that lets another user, or the working directory, replace
+    /// programs.
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        })
+    }
+}
+
+/// Why an entry was reported by [`PathEnv::audit`].
+///
+/// [`PathEnv::audit`]: struct.PathEnv.html#method.audit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AuditIssue {
+    /// The entry is empty, which most shells treat as the current directory.
+    Empty,
+    /// The entry is relative, such as `.` or `bin`, so it depends on the
+    /// current directory.
+    Relative,
+    /// The directory is writable by all users and does not have the sticky
+    /// bit set.
+    WorldWritable,
+    /// The directory is writable by its group and does not have the sticky
+    /// bit set.
+    GroupWritable,
+    /// The directory is owned by a user other than root and the current
+    /// user.
+    ForeignOwner {
+        /// The user ID of the owner.
+        uid: u32,
+    },
+    /// The entry is in a shared temporary directory, such as `/tmp`.
+    Temporary,
+}
+
+impl AuditIssue {
+    /// Returns how serious this issue is.
+    #[inline]
+    pub fn severity(&self) -> Severity {
+        match self {
+            AuditIssue::Empty
+            | AuditIssue::Relative
+            | AuditIssue::WorldWritable => Severity::High,
+            AuditIssue::ForeignOwner { .. } | AuditIssue::Temporary => {
+                Severity::Medium
+            }
+            // Groups such as `admin` or `staff` are often trusted on purpose.
+            AuditIssue::GroupWritable => Severity::Low,
+        }
+    }
+}
+
+impl fmt::Display for AuditIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditIssue::Empty => {
+                f.write_str("empty entry means the current directory")
+            }
+            AuditIssue::Relative => {
+                f.write_str("relative to the current directory")
+            }
+            AuditIssue::WorldWritable => f.write_str("writable by all users"),
+            AuditIssue::GroupWritable => f.write_str("writable by its group"),
+            AuditIssue::ForeignOwner { uid } => {
+                write!(f, "owned by another user ({})", uid)
+            }
+            AuditIssue::Temporary => f.write_str("in a temporary directory"),
+        }
+    }
+}
+
+/// A risky entry reported by [`PathEnv::audit`].
+///
+/// [`PathEnv::audit`]: struct.PathEnv.html#method.audit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AuditFinding<'a> {
+    index: usize,
+    path: &'a Path,
+    issue: AuditIssue,
+}
+
+impl<'a> AuditFinding<'a> {
+    /// Returns the index of the entry, counting empty entries.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the entry, which is empty for [`AuditIssue::Empty`].
+    ///
+    /// [`AuditIssue::Empty`]: enum.AuditIssue.html#variant.Empty
+    #[inline]
+    pub fn path(&self) -> &'a Path {
+        self.path
+    }
+
+    /// Returns what is wrong with the entry.
+    #[inline]
+    pub fn issue(&self) -> AuditIssue {
+        self.issue
+    }
+
+    /// Returns how serious the issue is.
+    #[inline]
+    pub fn severity(&self) -> Severity {
+        self.issue.severity()
+    }
+}
+
+impl fmt::Display for AuditFinding<'_> {
+    /// Writes a line such as
+    /// `high: entry 2 (/tmp/bin) is writable by all users`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.issue {
+            AuditIssue::Empty => {
+                write!(f, "{}: entry {} is empty", self.severity(), self.index)
+            }
+            issue => write!(
+                f,
+                "{}: entry {} ({}) is {}",
+                self.severity(),
+                self.index,
+                self.path.display(),
+                issue
+            ),
+        }
+    }
+}
+
+/// The risky entries of a [`PathEnv`].
+///
+/// This is returned by [`PathEnv::audit`]. Findings are in the order of the
+/// entries, and an entry may have several.
+///
+/// [`PathEnv`]:        struct.PathEnv.html
+/// [`PathEnv::audit`]: struct.PathEnv.html#method.audit
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathAudit<'a> {
+    findings: Vec<AuditFinding<'a>>,
+}
+
+impl<'a> PathAudit<'a> {
+    /// Returns `true` if no risky entries were found.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns the number of findings.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    /// Returns an iterator over the findings.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, AuditFinding<'a>> {
+        self.findings.iter()
+    }
+
+    /// Returns the highest severity of all findings, or `None` if there are
+    /// none.
+    #[inline]
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(AuditFinding::severity).max()
+    }
+}
+
+impl<'a, 'r> IntoIterator for &'r PathAudit<'a> {
+    type Item = &'r AuditFinding<'a>;
+    type IntoIter = slice::Iter<'r, AuditFinding<'a>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for PathAudit<'_> {
+    /// Writes one finding per line.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, finding) in self.findings.iter().enumerate() {
+            if i != 0 {
+                f.write_str("\n")?;
+            }
+            finding.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shared temporary directories, which any user can create entries in.
+#[cfg(unix)]
+const TEMP_DIRS: &[&str] = &["/tmp", "/var/tmp", "/dev/shm"];
+
+/// Appends the issues of the absolute directory `path` to `issues`.
+#[cfg(unix)]
+fn audit_dir(path: &Path, issues: &mut Vec<AuditIssue>) {
+    use std::os::unix::fs::MetadataExt;
+
+    const STICKY: u32 = 0o1000;
+    const GROUP_WRITE: u32 = 0o020;
+    const OTHER_WRITE: u32 = 0o002;
+
+    if TEMP_DIRS.iter().any(|dir| path.starts_with(dir)) {
+        issues.push(AuditIssue::Temporary);
+    }
+
+    // Entries that do not exist are not searched. Symbolic links are followed
+    // since the permissions of their target are what matter.
+    let meta = match path.metadata() {
+        Ok(meta) if meta.is_dir() => meta,
+        _ => return,
+    };
+
+    let mode = meta.mode();
+    if mode & STICKY == 0 {
+        if mode & OTHER_WRITE != 0 {
+            issues.push(AuditIssue::WorldWritable);
+        } else if mode & GROUP_WRITE != 0 {
+            issues.push(AuditIssue::GroupWritable);
+        }
+    }
+
+    let uid = meta.uid();
+    // SAFETY: `geteuid` has no preconditions and cannot fail.
+    if uid != 0 && uid != unsafe { libc::geteuid() } {
+        issues.push(AuditIssue::ForeignOwner { uid });
+    }
+}
+
+impl PathEnv {
+    /// Returns the entries that let other users or the current directory
+    /// decide which programs are run.
+    ///
+    /// This reports:
+    ///
+    /// - Empty entries, which most shells treat as the current directory.
+    ///
+    /// - Relative entries, such as `.` or `bin`.
+    ///
+    /// - Directories writable by their group or all users, unless the sticky
+    ///   bit is set.
+    ///
+    /// - Directories owned by a user other than root and the current user.
+    ///
+    /// - Entries in `/tmp`, `/var/tmp` or `/dev/shm`.
+    ///
+    /// Only the first two are checked on Windows. Entries that do not exist
+    /// are only checked by name, and symbolic links are followed.
+    ///
+    /// Indices count every entry of [`as_os_str`], including empty ones, so
+    /// they only match [`get`] when there are none.
+    ///
+    /// # Examples
+    ///
+    /// Scripts that run with elevated privileges can refuse an unsafe `PATH`:
+    ///
+    /// ```
+    /// use path_env::{PathEnv, Severity};
+    ///
+    /// let path = PathEnv::from(format!("bin{}", path_env::separator::STR));
+    /// let audit = path.audit();
+    ///
+    /// assert_eq!(audit.len(), 2);
+    /// assert_eq!(audit.max_severity(), Some(Severity::High));
+    ///
+    /// for finding in &audit {
+    ///     eprintln!("{}", finding);
+    /// }
+    /// ```
+    ///
+    /// [`as_os_str`]: #method.as_os_str
+    /// [`get`]:       #method.get
+    pub fn audit(&self) -> PathAudit<'_> {
+        let mut findings = Vec::new();
+        let mut issues = Vec::new();
+
+        for (index, path) in vars::PATH.entries(self.as_os_str()).enumerate() {
+            if path.as_os_str().is_empty() {
+                issues.push(AuditIssue::Empty);
+            } else if !path.is_absolute() {
+                issues.push(AuditIssue::Relative);
+            } else {
+                #[cfg(unix)]
+                audit_dir(path, &mut issues);
+            }
+
+            findings.extend(issues.drain(..).map(|issue| AuditFinding {
+                index,
+                path,
+                issue,
+            }));
+        }
+
+        PathAudit { findings }
+    }
+}
@@ -0,0 +1,395 @@
+//! The `path-env` command-line tool for inspecting and editing `PATH`.
+
+use path_env::{shell::Shell, CommandExt, PathEnv, PathPatch};
+use std::{
+    collections::VecDeque,
+    env,
+    ffi::{OsStr, OsString},
+    io::{self, Read, Write},
+    process::{self, Command},
+};
+
+const USAGE: &str = "\
+Usage: path-env [--stdin] <command> [<args>]
+
+Reads PATH from the environment, or from standard input with --stdin.
+
+Commands:
+    list                        Print the entries, numbered from 0
+    which [-a] <program>        Print where <program> is found, or all
+                                matches with -a
+    add [--front|--back] <dir>...
+                                Add or move entries to the front (default) or
+                                back, and print the new value
+    remove <dir>...             Remove entries and print the new value
+    dedup                       Remove repeated entries and print the new value
+    diff <old>                  Print how PATH changed since <old>
+    export [--shell <shell>]    Print a statement that sets PATH, for the
+                                shell in $SHELL by default
+    exec [<edit>...] [--] <program> [<args>...]
+                                Run <program> as found in the edited PATH,
+                                with PATH set to it
+
+Edits for exec, applied in order:
+    --prepend <dir>             Add or move <dir> to the front
+    --append <dir>              Add or move <dir> to the back
+    --remove <dir>              Remove <dir>
+    --dedup                     Remove repeated entries
+";
+
+/// Why the program failed, which determines its exit code.
+enum Failure {
+    /// The arguments are invalid. Exits with 2.
+    Usage(String),
+    /// A lookup found nothing. Exits with 1 without a message.
+    NotFound,
+    /// Anything else. Exits with 1.
+    Other(String),
+    /// The program for `exec` was not found. Exits with 127.
+    ProgramNotFound(String),
+    /// The program for `exec` could not be run. Exits with 126.
+    Exec(String),
+    /// The program for `exec` exited with the given code.
+    #[cfg(not(unix))]
+    Exit(i32),
+}
+
+impl From<io::Error> for Failure {
+    fn from(error: io::Error) -> Self {
+        Failure::Other(error.to_string())
+    }
+}
+
+/// The remaining command-line arguments.
+struct Args {
+    args: VecDeque<OsString>,
+}
+
+impl Args {
+    /// Removes and returns the next argument.
+    fn next(&mut self) -> Option<OsString> {
+        self.args.pop_front()
+    }
+
+    /// Removes the next argument if it is `flag`.
+    fn flag(&mut self, flag: &str) -> bool {
+        match self.args.front() {
+            Some(arg) if arg == flag => {
+                self.args.pop_front();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes and returns the value after `option`, if it is next.
+    fn option(&mut self, option: &str) -> Result<Option<OsString>, Failure> {
+        if !self.flag(option) {
+            return Ok(None);
+        }
+        match self.next() {
+            Some(value) => Ok(Some(value)),
+            None => Err(Failure::Usage(format!("{} requires a value", option))),
+        }
+    }
+
+    /// Removes and returns all remaining arguments, requiring at least one.
+    fn rest(&mut self, what: &str) -> Result<Vec<OsString>, Failure> {
+        self.flag("--");
+        if self.args.is_empty() {
+            return Err(Failure::Usage(format!("missing {}", what)));
+        }
+        Ok(self.args.drain(..).collect())
+    }
+
+    /// Removes and returns the only remaining argument.
+    fn single(&mut self, what: &str) -> Result<OsString, Failure> {
+        let mut rest = self.rest(what)?;
+        if rest.len() > 1 {
+            return Err(unexpected(&rest[1]));
+        }
+        Ok(rest.remove(0))
+    }
+
+    /// Fails if any arguments remain.
+    fn finish(&mut self) -> Result<(), Failure> {
+        match self.next() {
+            Some(arg) => Err(unexpected(&arg)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn unexpected(arg: &OsStr) -> Failure {
+    Failure::Usage(format!("unexpected argument {:?}", arg))
+}
+
+/// Reads `PATH` from standard input, without the trailing line break.
+fn read_stdin() -> io::Result<PathEnv> {
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input)?;
+
+    while let Some(b'\n') | Some(b'\r') = input.last() {
+        input.pop();
+    }
+
+    #[cfg(unix)]
+    let input = {
+        use std::os::unix::ffi::OsStringExt;
+        OsString::from_vec(input)
+    };
+
+    #[cfg(not(unix))]
+    let input = match String::from_utf8(input) {
+        Ok(input) => OsString::from(input),
+        Err(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "standard input is not valid UTF-8",
+            ))
+        }
+    };
+
+    Ok(input.into())
+}
+
+/// Writes `s` followed by a line break.
+fn write_line<W: Write>(out: &mut W, s: &OsStr) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        out.write_all(s.as_bytes())?;
+    }
+
+    #[cfg(not(unix))]
+    out.write_all(s.to_string_lossy().as_bytes())?;
+
+    out.write_all(b"\n")
+}
+
+/// Returns `path` without repeated entries, keeping the first of each.
+fn dedup(path: &PathEnv) -> PathEnv {
+    let mut seen = Vec::new();
+    path.iter()
+        .filter(|entry| {
+            let is_new = !seen.contains(entry);
+            if is_new {
+                seen.push(*entry);
+            }
+            is_new
+        })
+        .collect()
+}
+
+/// Applies the edits at the front of `args` to `path`, then runs the program
+/// that follows them.
+fn exec(args: &mut Args, mut path: PathEnv) -> Result<(), Failure> {
+    loop {
+        let mut patch = PathPatch::new();
+        if let Some(dir) = args.option("--prepend")? {
+            patch.prepend(dir);
+        } else if let Some(dir) = args.option("--append")? {
+            patch.append(dir);
+        } else if let Some(dir) = args.option("--remove")? {
+            patch.remove(dir);
+        } else if args.flag("--dedup") {
+            path = dedup(&path);
+            continue;
+        } else {
+            break;
+        }
+        patch.apply(&mut path);
+    }
+
+    let mut rest = args.rest("program")?.into_iter();
+    let program = rest.next().unwrap_or_default();
+
+    let mut command = Command::with_path_env(&program, &path)
+        .map_err(|error| Failure::ProgramNotFound(error.to_string()))?;
+    command.args(rest);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt as _;
+
+        // Only returns if the program could not be run.
+        let error = command.arg0(&program).exec();
+        Err(Failure::Exec(format!("{:?}: {}", program, error)))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let status = command.status().map_err(|error| {
+            Failure::Exec(format!("{:?}: {}", program, error))
+        })?;
+        match status.code() {
+            Some(0) => Ok(()),
+            Some(code) => Err(Failure::Exit(code)),
+            None => Err(Failure::Exit(1)),
+        }
+    }
+}
+
+fn run(mut args: Args) -> Result<(), Failure> {
+    let path = if args.flag("--stdin") {
+        read_stdin()?
+    } else {
+        PathEnv::from_var().unwrap_or_else(PathEnv::empty)
+    };
+
+    let command = match args.next() {
+        Some(command) => command,
+        None => return Err(Failure::Usage("missing command".to_owned())),
+    };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    match command.to_str().unwrap_or_default() {
+        "list" => {
+            args.finish()?;
+            let width = path.len().saturating_sub(1).to_string().len();
+            for (i, entry) in path.iter().enumerate() {
+                write!(out, "{:>width$}  ", i, width = width)?;
+                write_line(&mut out, entry.as_os_str())?;
+            }
+        }
+        "which" => {
+            let all = args.flag("-a");
+            let program = args.single("program")?;
+
+            let limit = if all { usize::MAX } else { 1 };
+            let mut found = false;
+            for program in path.which_all(&program).take(limit) {
+                found = true;
+                write_line(&mut out, program.as_os_str())?;
+            }
+            if !found {
+                return Err(Failure::NotFound);
+            }
+        }
+        "add" => {
+            let (mut front, mut back) = (false, false);
+            loop {
+                if args.flag("--front") {
+                    front = true;
+                } else if args.flag("--back") {
+                    back = true;
+                } else {
+                    break;
+                }
+            }
+            if back && front {
+                return Err(Failure::Usage(
+                    "--front and --back are exclusive".to_owned(),
+                ));
+            }
+
+            let dirs = args.rest("directory")?;
+            let mut patch = PathPatch::new();
+            if back {
+                for dir in dirs {
+                    patch.append(dir);
+                }
+            } else {
+                // Prepending in reverse keeps the order of `dirs`.
+                for dir in dirs.into_iter().rev() {
+                    patch.prepend(dir);
+                }
+            }
+
+            let mut path = path;
+            patch.apply(&mut path);
+            write_line(&mut out, path.as_os_str())?;
+        }
+        "remove" => {
+            let mut patch = PathPatch::new();
+            for dir in args.rest("directory")? {
+                patch.remove(dir);
+            }
+
+            let mut path = path;
+            patch.apply(&mut path);
+            write_line(&mut out, path.as_os_str())?;
+        }
+        "dedup" => {
+            args.finish()?;
+            write_line(&mut out, dedup(&path).as_os_str())?;
+        }
+        "diff" => {
+            let old = PathEnv::from(args.single("old PATH")?);
+            for change in &old.diff(&path) {
+                writeln!(out, "{}", change)?;
+            }
+        }
+        "export" => {
+            let shell = match args.option("--shell")? {
+                Some(shell) => Some(shell),
+                None => env::var_os("SHELL"),
+            };
+            args.finish()?;
+
+            let shell = match shell {
+                Some(shell) => shell
+                    .to_str()
+                    .unwrap_or_default()
+                    .parse::<Shell>()
+                    .map_err(|error| Failure::Usage(error.to_string()))?,
+                None if cfg!(windows) => Shell::PowerShell,
+                None => Shell::Sh,
+            };
+
+            let export = shell
+                .export(&path)
+                .map_err(|error| Failure::Other(error.to_string()))?;
+            writeln!(out, "{}", export)?;
+        }
+        "exec" => {
+            drop(out);
+            return exec(&mut args, path);
+        }
+        "help" | "--help" | "-h" => {
+            out.write_all(USAGE.as_bytes())?;
+        }
+        _ => {
+            return Err(Failure::Usage(format!(
+                "unknown command {:?}",
+                command
+            )))
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn main() {
+    let args = Args {
+        args: env::args_os().skip(1).collect(),
+    };
+
+    let code = match run(args) {
+        Ok(()) => 0,
+        Err(Failure::Usage(message)) => {
+            eprintln!("path-env: {}\n\n{}", message, USAGE);
+            2
+        }
+        Err(Failure::NotFound) => 1,
+        Err(Failure::Other(message)) => {
+            eprintln!("path-env: {}", message);
+            1
+        }
+        Err(Failure::ProgramNotFound(message)) => {
+            eprintln!("path-env: {}", message);
+            127
+        }
+        Err(Failure::Exec(message)) => {
+            eprintln!("path-env: {}", message);
+            126
+        }
+        #[cfg(not(unix))]
+        Err(Failure::Exit(code)) => code,
+    };
+
+    process::exit(code);
+}
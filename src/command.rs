@@ -0,0 +1,193 @@
+use crate::PathEnv;
+use std::{
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt, io,
+    process::Command,
+};
+
+/// Extensions to [`Command`] for running programs under a [`PathEnv`].
+///
+/// # Examples
+///
+/// The program is looked up in the new `PATH` rather than the one of the
+/// current process:
+///
+/// ```
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use path_env::CommandExt;
+/// use std::process::Command;
+///
+/// let mut path = path_env::PathEnv::empty();
+/// path.push_back("/path/to/bin");
+///
+/// Command::with_path_env("script.sh", &path)?.spawn()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Command`]: https://doc.rust-lang.org/std/process/struct.Command.html
+/// [`PathEnv`]: struct.PathEnv.html
+pub trait CommandExt: Sized {
+    /// Creates a command for `program` as found in `path`, with `PATH` set to
+    /// `path` for the child.
+    ///
+    /// See [`PathEnv::which`] for how `program` is looked up.
+    ///
+    /// [`PathEnv::which`]: struct.PathEnv.html#method.which
+    fn with_path_env<S: AsRef<OsStr>>(
+        program: S,
+        path: &PathEnv,
+    ) -> Result<Self, ProgramNotFound>;
+
+    /// Sets `PATH` to `path` for the child.
+    ///
+    /// Depending on the platform, the program may still be looked up in the
+    /// `PATH` of the current process. Use [`with_path_env`] to look it up in
+    /// `path` instead.
+    ///
+    /// [`with_path_env`]: #tymethod.with_path_env
+    fn path_env(&mut self, path: &PathEnv) -> &mut Self;
+}
+
+impl CommandExt for Command {
+    fn with_path_env<S: AsRef<OsStr>>(
+        program: S,
+        path: &PathEnv,
+    ) -> Result<Self, ProgramNotFound> {
+        let program = program.as_ref();
+        match path.which(program) {
+            Some(found) => {
+                let mut command = Command::new(found);
+                command.path_env(path);
+                Ok(command)
+            }
+            None => Err(ProgramNotFound {
+                program: program.to_owned(),
+            }),
+        }
+    }
+
+    #[inline]
+    fn path_env(&mut self, path: &PathEnv) -> &mut Self {
+        self.env("PATH", path)
+    }
+}
+
+/// Returns whether `name` refers to the `PATH` environment variable.
+#[cfg(windows)]
+fn is_path_var(name: &OsStr) -> bool {
+    name.to_str()
+        .map_or(false, |name| name.eq_ignore_ascii_case("PATH"))
+}
+
+/// Returns whether `name` refers to the `PATH` environment variable.
+#[cfg(not(windows))]
+fn is_path_var(name: &OsStr) -> bool {
+    name == "PATH"
+}
+
+impl PathEnv {
+    /// Returns the `PATH` that a child spawned from `command` will see, or
+    /// `None` if it will not have one.
+    ///
+    /// This takes into account variables set with [`Command::env`] and
+    /// removed with [`Command::env_remove`]. If `command` does not change
+    /// `PATH`, the child inherits it from the current process and this is the
+    /// same as [`from_var`].
+    ///
+    /// [`Command`] does not report whether [`Command::env_clear`] was called.
+    /// Use [`for_command_with`] for commands with a cleared environment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::process::Command;
+    ///
+    /// let mut command = Command::new("ls");
+    /// command.env_remove("PATH");
+    ///
+    /// assert_eq!(path_env::PathEnv::for_command(&command), None);
+    /// ```
+    ///
+    /// [`Command`]:             https://doc.rust-lang.org/std/process/struct.Command.html
+    /// [`Command::env`]:        https://doc.rust-lang.org/std/process/struct.Command.html#method.env
+    /// [`Command::env_remove`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.env_remove
+    /// [`Command::env_clear`]:  https://doc.rust-lang.org/std/process/struct.Command.html#method.env_clear
+    /// [`from_var`]:            #method.from_var
+    /// [`for_command_with`]:    #method.for_command_with
+    #[inline]
+    pub fn for_command(command: &Command) -> Option<Self> {
+        Self::for_command_with(command, false)
+    }
+
+    /// Returns the `PATH` that a child spawned from `command` will see, where
+    /// `env_cleared` tells whether [`Command::env_clear`] was called on it.
+    ///
+    /// If the environment was cleared and `PATH` was not set again, the child
+    /// has no `PATH` and this returns `None`. Otherwise, this is the same as
+    /// [`for_command`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use path_env::PathEnv;
+    /// use std::process::Command;
+    ///
+    /// let mut command = Command::new("ls");
+    /// command.env_clear();
+    /// assert_eq!(PathEnv::for_command_with(&command, true), None);
+    ///
+    /// command.env("PATH", "/bin");
+    /// assert_eq!(
+    ///     PathEnv::for_command_with(&command, true).unwrap(),
+    ///     *"/bin"
+    /// );
+    /// ```
+    ///
+    /// [`Command::env_clear`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.env_clear
+    /// [`for_command`]:        #method.for_command
+    pub fn for_command_with(
+        command: &Command,
+        env_cleared: bool,
+    ) -> Option<Self> {
+        let path = command.get_envs().find(|(name, _)| is_path_var(name));
+
+        match path {
+            Some((_, value)) => value.map(|value| value.to_owned().into()),
+            None if env_cleared => None,
+            None => Self::from_var(),
+        }
+    }
+}
+
+/// The error returned when a program cannot be found in a [`PathEnv`].
+///
+/// [`PathEnv`]: struct.PathEnv.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramNotFound {
+    program: OsString,
+}
+
+impl ProgramNotFound {
+    /// Returns the name of the program that was looked up.
+    #[inline]
+    pub fn program(&self) -> &OsStr {
+        &self.program
+    }
+}
+
+impl fmt::Display for ProgramNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "program not found in PATH: {:?}", self.program)
+    }
+}
+
+impl Error for ProgramNotFound {}
+
+impl From<ProgramNotFound> for io::Error {
+    #[inline]
+    fn from(error: ProgramNotFound) -> Self {
+        io::Error::new(io::ErrorKind::NotFound, error)
+    }
+}
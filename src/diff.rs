@@ -0,0 +1,212 @@
+use crate::PathEnv;
+use std::{fmt, path::Path, slice};
+
+/// A single difference between two [`PathEnv`] values.
+///
+/// [`PathEnv`]: struct.PathEnv.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathChange<'a> {
+    /// `path` was added at `index` in the new value.
+    Added {
+        /// The index in the new value.
+        index: usize,
+        /// The entry that was added.
+        path: &'a Path,
+    },
+    /// `path` was removed from `index` in the old value.
+    Removed {
+        /// The index in the old value.
+        index: usize,
+        /// The entry that was removed.
+        path: &'a Path,
+    },
+    /// `path` was moved relative to the entries around it.
+    Moved {
+        /// The index in the old value.
+        from: usize,
+        /// The index in the new value.
+        to: usize,
+        /// The entry that was moved.
+        path: &'a Path,
+    },
+}
+
+impl PathChange<'_> {
+    /// Returns the entry affected by this change.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        match *self {
+            PathChange::Added { path, .. }
+            | PathChange::Removed { path, .. }
+            | PathChange::Moved { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for PathChange<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PathChange::Added { index, path } => {
+                write!(f, "+{} at {}", path.display(), index)
+            }
+            PathChange::Removed { path, .. } => {
+                write!(f, "-{}", path.display())
+            }
+            PathChange::Moved { from, to, path } => {
+                write!(f, ">{} from {} to {}", path.display(), from, to)
+            }
+        }
+    }
+}
+
+/// The differences between two [`PathEnv`] values.
+///
+/// This is returned by [`PathEnv::diff`]. Removals come first in the order of
+/// the old value, followed by additions and moves in the order of the new
+/// value.
+///
+/// [`PathEnv`]:       struct.PathEnv.html
+/// [`PathEnv::diff`]: struct.PathEnv.html#method.diff
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathDiff<'a> {
+    changes: Vec<PathChange<'a>>,
+}
+
+impl<'a> PathDiff<'a> {
+    /// Returns `true` if the values had the same entries in the same order.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the number of changes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns an iterator over the changes.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, PathChange<'a>> {
+        self.changes.iter()
+    }
+}
+
+impl<'a, 'd> IntoIterator for &'d PathDiff<'a> {
+    type Item = &'d PathChange<'a>;
+    type IntoIter = slice::Iter<'d, PathChange<'a>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for PathDiff<'_> {
+    /// Writes the changes separated by commas, such as
+    /// `+/path/to/bin at 0, -/opt/old/bin`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, change) in self.changes.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            change.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether each entry of `old` and `new` is part of their longest
+/// common subsequence.
+fn lcs(old: &[&Path], new: &[&Path]) -> (Vec<bool>, Vec<bool>) {
+    let (n, m) = (old.len(), new.len());
+
+    // `table[i][j]` is the LCS length of `old[i..]` and `new[j..]`.
+    let mut table = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if old[i] == new[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut in_old = vec![false; n];
+    let mut in_new = vec![false; m];
+    let (mut i, mut j) = (0, 0);
+
+    while i < n && j < m {
+        if old[i] == new[j] {
+            in_old[i] = true;
+            in_new[j] = true;
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    (in_old, in_new)
+}
+
+impl PathEnv {
+    /// Returns the entries added, removed and moved to get from `self` to
+    /// `other`.
+    ///
+    /// Entries are compared structurally, as in [`contains`]. Entries that
+    /// keep their relative order are left out; an entry that is removed in
+    /// one place and added in another is reported as moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use path_env::PathEnv;
+    ///
+    /// let old: PathEnv = vec!["/opt/old/bin", "/usr/bin"].into_iter().collect();
+    /// let new: PathEnv = vec!["/home/me/.cargo/bin", "/usr/bin"]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.to_string(), "-/opt/old/bin, +/home/me/.cargo/bin at 0");
+    /// ```
+    ///
+    /// [`contains`]: #method.contains
+    pub fn diff<'a>(&'a self, other: &'a PathEnv) -> PathDiff<'a> {
+        let old = self.iter().collect::<Vec<_>>();
+        let new = other.iter().collect::<Vec<_>>();
+        let (in_old, in_new) = lcs(&old, &new);
+
+        let mut removed =
+            (0..old.len()).filter(|&i| !in_old[i]).collect::<Vec<_>>();
+        let mut inserted = Vec::new();
+
+        for (to, &path) in new.iter().enumerate() {
+            if in_new[to] {
+                continue;
+            }
+            match removed.iter().position(|&from| old[from] == path) {
+                Some(pos) => {
+                    let from = removed.remove(pos);
+                    inserted.push(PathChange::Moved { from, to, path });
+                }
+                None => inserted.push(PathChange::Added { index: to, path }),
+            }
+        }
+
+        let mut changes = removed
+            .into_iter()
+            .map(|index| PathChange::Removed {
+                index,
+                path: old[index],
+            })
+            .collect::<Vec<_>>();
+        changes.extend(inserted);
+
+        PathDiff { changes }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{PathDiff, PathEnv};
+use std::fmt;
+
+/// The `PATH` of the current process when it started and now.
+///
+/// Libraries and plugins sometimes change `PATH` at runtime. Comparing against
+/// the value the process started with shows what was changed, which can be
+/// logged to explain why a program is or is not found.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(target_os = "linux")] {
+/// use path_env::PathDrift;
+///
+/// let drift = PathDrift::detect()?;
+/// if drift.has_drifted() {
+///     eprintln!("{}", drift);
+/// }
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// On other platforms, take a snapshot early in `main` instead:
+///
+/// ```
+/// use path_env::{PathDrift, PathEnv};
+///
+/// let initial = PathEnv::from_var();
+/// // ...
+/// let drift = PathDrift::new(initial, PathEnv::from_var());
+/// # assert!(!drift.has_drifted());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PathDrift {
+    // Unset values are stored as empty so that `diff` can borrow them.
+    initial: PathEnv,
+    current: PathEnv,
+    was_set: bool,
+    is_set: bool,
+}
+
+impl PathDrift {
+    /// Compares `initial` to `current`, where `None` means `PATH` was not
+    /// set.
+    pub fn new(initial: Option<PathEnv>, current: Option<PathEnv>) -> Self {
+        Self {
+            was_set: initial.is_some(),
+            is_set: current.is_some(),
+            initial: initial.unwrap_or_else(PathEnv::empty),
+            current: current.unwrap_or_else(PathEnv::empty),
+        }
+    }
+
+    /// Compares the `PATH` the current process started with, from
+    /// `/proc/self/environ`, to its current `PATH`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[cfg_attr(
+        feature = "_doc-cfg",
+        doc(cfg(any(target_os = "linux", target_os = "android")))
+    )]
+    pub fn detect() -> std::io::Result<Self> {
+        let block = std::fs::read("/proc/self/environ")?;
+        let initial = PathEnv::from_environ(&block);
+        let current = crate::lock::var_os("PATH").map(PathEnv::from);
+        Ok(Self::new(initial, current))
+    }
+
+    /// Returns the `PATH` the process started with, or `None` if it was not
+    /// set.
+    #[inline]
+    pub fn initial(&self) -> Option<&PathEnv> {
+        if self.was_set {
+            Some(&self.initial)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the current `PATH`, or `None` if it is not set.
+    #[inline]
+    pub fn current(&self) -> Option<&PathEnv> {
+        if self.is_set {
+            Some(&self.current)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if `PATH` changed in any way, including being set or
+    /// removed.
+    #[inline]
+    pub fn has_drifted(&self) -> bool {
+        self.was_set != self.is_set
+            || self.initial.as_os_str() != self.current.as_os_str()
+    }
+
+    /// Returns the entries added, removed and moved since startup.
+    ///
+    /// An unset `PATH` is treated as empty. See [`PathEnv::diff`] for more
+    /// info.
+    ///
+    /// [`PathEnv::diff`]: struct.PathEnv.html#method.diff
+    #[inline]
+    pub fn diff(&self) -> PathDiff<'_> {
+        self.initial.diff(&self.current)
+    }
+}
+
+impl fmt::Display for PathDrift {
+    /// Writes a summary such as
+    /// `PATH was modified after startup: +/opt/plugin/bin at 0`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.was_set, self.is_set) {
+            _ if !self.has_drifted() => {
+                f.write_str("PATH is unchanged since startup")
+            }
+            (true, false) => f.write_str("PATH was removed after startup"),
+            (false, true) => {
+                write!(f, "PATH was set after startup: {}", self.diff())
+            }
+            _ => {
+                let diff = self.diff();
+                if diff.is_empty() {
+                    // Only separators or empty entries changed.
+                    write!(
+                        f,
+                        "PATH was modified after startup: {:?} -> {:?}",
+                        self.initial.as_os_str(),
+                        self.current.as_os_str()
+                    )
+                } else {
+                    write!(f, "PATH was modified after startup: {}", diff)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use crate::{lock, sys::byte_repr::ByteRepr, PathEnv};
+use std::{
+    ffi::{OsStr, OsString},
+    path::{self, Path, PathBuf},
+};
+
+/// The environment variable that `~` refers to.
+#[cfg(windows)]
+const HOME_VAR: &str = "USERPROFILE";
+
+/// The environment variable that `~` refers to.
+#[cfg(not(windows))]
+const HOME_VAR: &str = "HOME";
+
+/// Rules for writing entries of a [`PathEnv`] in symbolic form.
+///
+/// Entries under the home directory are written as `~`, and entries under a
+/// registered variable's value are written as `$NAME` on Unix-like systems or
+/// `%NAME%` on Windows. The longest matching prefix wins.
+///
+/// The result of [`PathEnv::contract`] can be parsed back with
+/// [`PathEnv::expand`], unless an entry already contains text that `expand`
+/// treats as a reference, such as a literal `$HOME` or a leading `~`. There
+/// is no way to escape these, so they are expanded too.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(unix)] {
+/// use path_env::{Contraction, PathEnv};
+///
+/// let path = PathEnv::from(String::from("/home/me/.cargo/bin:/home/me/bin"));
+///
+/// let mut rules = Contraction::new();
+/// rules.home("/home/me").var("CARGO_HOME", "/home/me/.cargo");
+///
+/// let short = path.contract(&rules);
+/// assert_eq!(short, "$CARGO_HOME/bin:~/bin");
+/// # }
+/// ```
+///
+/// [`PathEnv`]:           struct.PathEnv.html
+/// [`PathEnv::contract`]: struct.PathEnv.html#method.contract
+/// [`PathEnv::expand`]:   struct.PathEnv.html#method.expand
+#[derive(Clone, Debug, Default)]
+pub struct Contraction {
+    home: Option<PathBuf>,
+    vars: Vec<(String, PathBuf)>,
+}
+
+impl Contraction {
+    /// Creates an empty set of rules that leaves all entries as-is.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates rules that contract the current user's home directory.
+    ///
+    /// This is `HOME` on Unix-like systems and `USERPROFILE` on Windows.
+    pub fn from_env() -> Self {
+        let mut rules = Self::new();
+        if let Some(home) = lock::var_os(HOME_VAR) {
+            rules.home(home);
+        }
+        rules
+    }
+
+    /// Sets the directory to write as `~`.
+    #[inline]
+    pub fn home<P: Into<PathBuf>>(&mut self, home: P) -> &mut Self {
+        self.home = Some(home.into());
+        self
+    }
+
+    /// Writes entries under `value` in terms of the variable `name`.
+    ///
+    /// The rule is ignored if `name` cannot be referenced in a way that
+    /// [`PathEnv::expand`] understands, such as `MY-TOOLS` or `1X` on
+    /// Unix-like systems.
+    ///
+    /// [`PathEnv::expand`]: struct.PathEnv.html#method.expand
+    #[inline]
+    pub fn var<N, P>(&mut self, name: N, value: P) -> &mut Self
+    where
+        N: Into<String>,
+        P: Into<PathBuf>,
+    {
+        self.vars.push((name.into(), value.into()));
+        self
+    }
+
+    /// Writes entries in terms of the variable `name` if it is currently set.
+    pub fn env_var<N: Into<String>>(&mut self, name: N) -> &mut Self {
+        let name = name.into();
+        match lock::var_os(&name) {
+            Some(value) => self.var(name, value),
+            None => self,
+        }
+    }
+
+    /// Returns the symbolic form of `part`, or `None` if no rule applies.
+    fn contract_part(&self, part: &Path) -> Option<OsString> {
+        let home = self.home.as_ref().map(|home| ("~".into(), home));
+        let vars = self
+            .vars
+            .iter()
+            .filter(|(name, _)| is_var_name(name))
+            .map(|(name, value)| (symbol(name), value));
+
+        let mut best: Option<(usize, OsString, &Path)> = None;
+
+        for (symbol, value) in home.into_iter().chain(vars) {
+            // Contracting `/` or an empty value would only obscure entries.
+            if value.parent().is_none() {
+                continue;
+            }
+            if let Ok(rest) = part.strip_prefix(value) {
+                let len = value.as_os_str().len();
+                match best {
+                    Some((best_len, ..)) if best_len >= len => {}
+                    _ => best = Some((len, symbol, rest)),
+                }
+            }
+        }
+
+        best.map(|(_, mut symbol, rest)| {
+            if !rest.as_os_str().is_empty() {
+                symbol.push(path::MAIN_SEPARATOR.to_string());
+                symbol.push(rest);
+            }
+            symbol
+        })
+    }
+}
+
+#[cfg(windows)]
+fn symbol(name: &str) -> OsString {
+    format!("%{}%", name).into()
+}
+
+#[cfg(not(windows))]
+fn symbol(name: &str) -> OsString {
+    format!("${}", name).into()
+}
+
+/// Returns `true` if `name` can be written as a reference that `var_ref`
+/// parses back.
+#[cfg(windows)]
+fn is_var_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('%')
+}
+
+/// Returns `true` if `name` can be written as a reference that `var_ref`
+/// parses back.
+#[cfg(not(windows))]
+fn is_var_name(name: &str) -> bool {
+    let mut bytes = name.bytes();
+    match bytes.next() {
+        Some(b) if b == b'_' || b.is_ascii_alphabetic() => {
+            bytes.all(|b| b == b'_' || b.is_ascii_alphanumeric())
+        }
+        _ => false,
+    }
+}
+
+/// Returns the variable name and the length of the reference at the start of
+/// `bytes`, if any.
+#[cfg(windows)]
+fn var_ref(bytes: &[u8]) -> Option<(&[u8], usize)> {
+    if bytes.first() != Some(&b'%') {
+        return None;
+    }
+    let len = bytes[1..].iter().position(|&b| b == b'%')?;
+    if len == 0 {
+        None
+    } else {
+        Some((&bytes[1..][..len], len + 2))
+    }
+}
+
+/// Returns the variable name and the length of the reference at the start of
+/// `bytes`, if any.
+#[cfg(not(windows))]
+fn var_ref(bytes: &[u8]) -> Option<(&[u8], usize)> {
+    fn name_len(bytes: &[u8]) -> usize {
+        match bytes.first() {
+            Some(&b) if b == b'_' || b.is_ascii_alphabetic() => bytes
+                .iter()
+                .position(|&b| !(b == b'_' || b.is_ascii_alphanumeric()))
+                .unwrap_or(bytes.len()),
+            _ => 0,
+        }
+    }
+
+    if bytes.first() != Some(&b'$') {
+        return None;
+    }
+    let rest = &bytes[1..];
+
+    if rest.first() == Some(&b'{') {
+        let len = name_len(&rest[1..]);
+        if len != 0 && rest.get(len + 1) == Some(&b'}') {
+            return Some((&rest[1..][..len], len + 3));
+        }
+        None
+    } else {
+        match name_len(rest) {
+            0 => None,
+            len => Some((&rest[..len], len + 1)),
+        }
+    }
+}
+
+/// Converts a slice of an entry back to an `OsStr`.
+///
+/// SAFETY: Entries are only ever split at ASCII bytes, which are always
+/// boundaries in the platform's `OsStr` encoding.
+#[inline]
+fn os_str(bytes: &[u8]) -> &OsStr {
+    unsafe { OsStr::from_bytes(bytes) }
+}
+
+fn expand_part<F>(part: &[u8], lookup: &mut F, out: &mut OsString)
+where
+    F: FnMut(&OsStr) -> Option<OsString>,
+{
+    let mut rest = part;
+
+    let is_home = match rest {
+        [b'~'] => true,
+        [b'~', b, ..] => path::is_separator(*b as char),
+        _ => false,
+    };
+    if is_home {
+        if let Some(home) = lookup(OsStr::new(HOME_VAR)) {
+            out.push(home);
+            rest = &rest[1..];
+        }
+    }
+
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < rest.len() {
+        match var_ref(&rest[i..]) {
+            Some((name, len)) => {
+                if let Some(value) = lookup(os_str(name)) {
+                    out.push(os_str(&rest[start..i]));
+                    out.push(value);
+                    start = i + len;
+                }
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+
+    out.push(os_str(&rest[start..]));
+}
+
+impl PathEnv {
+    /// Creates an instance from `path`, expanding `~` and variable references
+    /// in each entry using the current environment.
+    ///
+    /// See [`expand_with`](#method.expand_with) for the supported syntax.
+    #[inline]
+    pub fn expand<P: AsRef<OsStr>>(path: P) -> Self {
+        Self::expand_with(path, |name| lock::var_os(name))
+    }
+
+    /// Creates an instance from `path`, expanding `~` and variable references
+    /// in each entry by calling `lookup` with the variable's name.
+    ///
+    /// A leading `~` refers to `HOME` on Unix-like systems and `USERPROFILE`
+    /// on Windows. Variables are written as `$NAME` or `${NAME}` on Unix-like
+    /// systems and as `%NAME%` on Windows. References to unknown variables are
+    /// left as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(unix)] {
+    /// use path_env::PathEnv;
+    ///
+    /// let path = PathEnv::expand_with("~/bin:${CARGO_HOME}/bin", |name| {
+    ///     match name.to_str()? {
+    ///         "HOME" => Some("/home/me".into()),
+    ///         "CARGO_HOME" => Some("/opt/cargo".into()),
+    ///         _ => None,
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(path.as_os_str(), "/home/me/bin:/opt/cargo/bin");
+    /// # }
+    /// ```
+    pub fn expand_with<P, F>(path: P, mut lookup: F) -> Self
+    where
+        P: AsRef<OsStr>,
+        F: FnMut(&OsStr) -> Option<OsString>,
+    {
+        let path = path.as_ref();
+        let mut result = OsString::with_capacity(path.len());
+
+        for (i, part) in crate::split(path).enumerate() {
+            if i != 0 {
+                result.push(crate::separator::OS_STR);
+            }
+            expand_part(part.as_bytes(), &mut lookup, &mut result);
+        }
+
+        result.into()
+    }
+
+    /// Returns `self` with entries written in symbolic form according to
+    /// `rules`.
+    ///
+    /// This is meant for logs and user interfaces. The result can be parsed
+    /// back with [`expand`](#method.expand), except for entries that already
+    /// contain a variable reference or a leading `~`, which are written as-is
+    /// and thus expanded.
+    pub fn contract(&self, rules: &Contraction) -> OsString {
+        let mut result = OsString::with_capacity(self.path.len());
+
+        for (i, part) in self.iter().enumerate() {
+            if i != 0 {
+                result.push(crate::separator::OS_STR);
+            }
+            match rules.contract_part(part) {
+                Some(part) => result.push(part),
+                None => result.push(part),
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{lock, PathEnv};
+use std::{env, ffi::OsString, fmt};
+
+/// Restores the previous `PATH` environment variable when dropped.
+///
+/// This is returned by [`PathEnv::set_var_scoped`]. While it is alive, the
+/// current thread holds a crate-wide lock on the environment, so other threads
+/// that change or read `PATH` through this crate wait for it to be dropped.
+///
+/// Guards may be nested within the same thread, and should be dropped in the
+/// reverse order of creation.
+///
+/// [`PathEnv::set_var_scoped`]: struct.PathEnv.html#method.set_var_scoped
+#[must_use = "`PATH` is restored as soon as the guard is dropped"]
+pub struct PathVarGuard {
+    previous: Option<OsString>,
+    // Dropped after `previous` is restored in `Drop::drop`.
+    _lock: lock::ReentrantLockGuard<'static>,
+}
+
+impl fmt::Debug for PathVarGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PathVarGuard")
+            .field("previous", &self.previous)
+            .finish()
+    }
+}
+
+impl PathVarGuard {
+    /// Returns the value of `PATH` that will be restored, or `None` if it was
+    /// not set.
+    #[inline]
+    pub fn previous(&self) -> Option<&OsString> {
+        self.previous.as_ref()
+    }
+}
+
+impl Drop for PathVarGuard {
+    fn drop(&mut self) {
+        match &self.previous {
+            Some(previous) => env::set_var("PATH", previous),
+            None => env::remove_var("PATH"),
+        }
+    }
+}
+
+impl PathEnv {
+    /// Sets the `PATH` environment variable to `self` for the currently
+    /// running process until the returned guard is dropped.
+    ///
+    /// The previous value is restored when the guard is dropped, including
+    /// removing `PATH` if it was not set. This is useful for tests, which run
+    /// on multiple threads by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let original = std::env::var_os("PATH");
+    ///
+    /// let path: path_env::PathEnv = vec!["/path/to/bin"].into_iter().collect();
+    /// {
+    ///     let _guard = path.set_var_scoped();
+    ///     assert_eq!(path_env::var(), Some(path));
+    /// }
+    ///
+    /// assert_eq!(std::env::var_os("PATH"), original);
+    /// ```
+    pub fn set_var_scoped(&self) -> PathVarGuard {
+        let lock = lock::ENV.lock();
+        let previous = env::var_os("PATH");
+        env::set_var("PATH", &self.path);
+
+        PathVarGuard {
+            previous,
+            _lock: lock,
+        }
+    }
+}
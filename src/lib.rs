@@ -17,7 +17,28 @@
 //! # mod a {}
 //! ```
 //!
+//! # Thread Safety
+//!
+//! Functions in this crate that read or write the environment hold a
+//! crate-wide lock while doing so. Use [`ProcessPath`] to make several of
+//! these at once without other threads interleaving. Changing the environment
+//! without going through this crate is not covered by the lock.
+//!
+//! # Features
+//!
+//! - `serde`: Implements `Serialize` and `Deserialize` for [`PathEnv`]. It is
+//!   written as a list of entries in human-readable formats and as bytes in
+//!   binary formats, without losing entries that are not valid Unicode. It can
+//!   also be read from a single string of entries joined by the separator.
+//! - `profiles`: Adds named `PATH` profiles loaded from a TOML file. See
+//!   [`profile`] for more info.
+//! - `cli`: Builds the `path-env` binary for inspecting and editing `PATH`
+//!   from the command line. Run `path-env help` for its usage.
+//!
 //! [@NikolaiVazquez]: https://twitter.com/NikolaiVazquez
+//! [`PathEnv`]:         struct.PathEnv.html
+//! [`profile`]:         profile/index.html
+//! [`ProcessPath`]:     struct.ProcessPath.html
 
 // This `cfg` allows for the crate to compile on unsupported targets. However,
 // it won't be usable.
@@ -35,22 +56,70 @@ use std::{
     slice,
 };
 
+mod audit;
 mod cmp;
+mod command;
+mod diff;
+mod drift;
+mod expand;
+mod guard;
+mod lock;
+mod patch;
+mod process;
+mod prune;
+#[cfg(feature = "serde")]
+mod serde;
 mod split;
 mod sys;
 mod util;
+mod which;
 
 #[macro_use]
 mod macros;
 
+cfg_unix! {
+    mod login;
+}
+
 #[cfg(test)]
 mod tests;
 
 pub mod os;
+#[cfg(feature = "profiles")]
+#[cfg_attr(feature = "_doc-cfg", doc(cfg(feature = "profiles")))]
+pub mod profile;
 pub mod separator;
+pub mod shell;
+pub mod vars;
+
+cfg_unix! {
+    pub mod system;
+}
 
 use sys::byte_repr::{ByteBufRepr, ByteRepr};
 
+#[doc(inline)]
+pub use audit::{AuditFinding, AuditIssue, PathAudit, Severity};
+#[doc(inline)]
+pub use command::{CommandExt, ProgramNotFound};
+#[doc(inline)]
+pub use diff::{PathChange, PathDiff};
+#[doc(inline)]
+pub use drift::PathDrift;
+#[doc(inline)]
+pub use expand::Contraction;
+#[doc(inline)]
+pub use guard::PathVarGuard;
+#[cfg(any(unix, feature = "_doc-cfg"))]
+#[cfg_attr(feature = "_doc-cfg", doc(cfg(unix)))]
+#[doc(inline)]
+pub use login::ShellPathError;
+#[doc(inline)]
+pub use patch::{PatchOp, PathPatch};
+#[doc(inline)]
+pub use process::ProcessPath;
+#[doc(inline)]
+pub use prune::{PrunePolicy, PruneReason, PruneReport, PrunedEntry};
 #[doc(inline)]
 pub use split::{split, PathEnvSplit};
 
@@ -266,34 +335,85 @@ impl PathEnv {
     }
 
     /// Creates an instance by fetching the current `PATH` environment variable.
+    ///
+    /// Empty entries are ignored. Use [`from_var_named`] with [`vars::PATH`]
+    /// to read them as the current directory where that applies.
+    ///
+    /// [`from_var_named`]: #method.from_var_named
+    /// [`vars::PATH`]:     vars/constant.PATH.html
     #[inline]
     pub fn from_var() -> Option<Self> {
-        env::var_os("PATH").map(Self::from)
+        lock::var_os("PATH").map(Self::from)
     }
 
     cfg_unix! {
-        /// Returns the `PATH` returned by running `getconf PATH`.
+        /// Returns the default `PATH` reported by the system.
         ///
         /// This is usually used to get the default `PATH` on POSIX-compliant
-        /// systems.
+        /// systems. It comes from `confstr(_CS_PATH)` where available, and
+        /// from running `getconf PATH` otherwise.
         ///
         /// Use [`os::unix::getconf`] to get the [`OsString`] without parsing.
         ///
         /// [`os::unix::getconf`]: os/unix/fn.getconf.html
         /// [`OsString`]: https://doc.rust-lang.org/std/ffi/struct.OsString.html
-        pub fn from_getconf() -> std::io::Result<Self> {
+        pub fn from_getconf() -> Result<Self, os::unix::GetconfError> {
             os::unix::getconf().map(|path| path.into())
         }
+
+        /// Creates an instance from the `PATH` in an environment block, such
+        /// as the output of `env -0`.
+        ///
+        /// See [`os::unix::environ_vars`] for the format of `block`.
+        ///
+        /// [`os::unix::environ_vars`]: os/unix/fn.environ_vars.html
+        #[inline]
+        pub fn from_environ(block: &[u8]) -> Option<Self> {
+            os::unix::environ_var(block, "PATH").map(|path| path.to_owned().into())
+        }
+    }
+
+    /// Creates an instance from the `PATH` of the process with ID `pid`, or
+    /// returns `None` if it has no `PATH`.
+    ///
+    /// This reads `/proc/<pid>/environ`, which holds the environment that the
+    /// process started with. Changes the process made to its own environment
+    /// afterwards are not visible.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the process does not exist, or if its environment
+    /// may not be read, which is usually the case for processes of other users.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use path_env::PathEnv;
+    ///
+    /// let path = PathEnv::from_pid(std::process::id())?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[cfg_attr(
+        feature = "_doc-cfg",
+        doc(cfg(any(target_os = "linux", target_os = "android")))
+    )]
+    pub fn from_pid(pid: u32) -> std::io::Result<Option<Self>> {
+        let block = std::fs::read(format!("/proc/{}/environ", pid))?;
+        Ok(Self::from_environ(&block))
     }
 
     /// Sets the `PATH` environment variable to `self` for the currently running
     /// process.
     ///
-    /// See [`env::set_var`] for more info.
+    /// See [`env::set_var`] for more info. Use [`set_var_scoped`] to restore
+    /// the previous value afterward.
     ///
-    /// [`env::set_var`]: https://doc.rust-lang.org/std/env/fn.set_var.html
+    /// [`env::set_var`]:   https://doc.rust-lang.org/std/env/fn.set_var.html
+    /// [`set_var_scoped`]: #method.set_var_scoped
     #[inline]
     pub fn set_var(&self) {
+        let _lock = lock::ENV.lock();
         env::set_var("PATH", &self.path);
     }
 
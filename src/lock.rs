@@ -0,0 +1,92 @@
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    marker::PhantomData,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, ThreadId},
+};
+
+/// Serializes accesses to the environment made through this crate.
+pub static ENV: ReentrantLock = ReentrantLock::new();
+
+/// Fetches the environment variable `name` while holding `ENV`.
+pub fn var_os<K: AsRef<OsStr>>(name: K) -> Option<OsString> {
+    let _lock = ENV.lock();
+    env::var_os(name)
+}
+
+/// A lock that may be acquired again by the thread that holds it.
+///
+/// This allows for nesting scopes that each hold the lock, such as
+/// `PathVarGuard`s, within the same thread.
+pub struct ReentrantLock {
+    state: Mutex<State>,
+    released: Condvar,
+}
+
+struct State {
+    owner: Option<ThreadId>,
+    depth: usize,
+}
+
+/// Holds a `ReentrantLock` until dropped.
+///
+/// This is not `Send` because the lock is owned by the thread that acquired
+/// it.
+pub struct ReentrantLockGuard<'a> {
+    lock: &'a ReentrantLock,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ReentrantLock {
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                owner: None,
+                depth: 0,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panic while the state is held cannot leave it inconsistent.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn lock(&self) -> ReentrantLockGuard<'_> {
+        let current = thread::current().id();
+        let mut state = self.state();
+
+        loop {
+            match state.owner {
+                Some(owner) if owner != current => {
+                    state = self
+                        .released
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+                _ => break,
+            }
+        }
+
+        state.owner = Some(current);
+        state.depth += 1;
+
+        ReentrantLockGuard {
+            lock: self,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for ReentrantLockGuard<'_> {
+    fn drop(&mut self) {
+        let mut state = self.lock.state();
+        state.depth -= 1;
+        if state.depth == 0 {
+            state.owner = None;
+            self.lock.released.notify_one();
+        }
+    }
+}
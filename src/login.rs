@@ -0,0 +1,232 @@
+use crate::PathEnv;
+use std::{
+    error::Error,
+    ffi::OsStr,
+    fmt, io,
+    io::Read,
+    os::unix::ffi::OsStrExt,
+    process::{Command, ExitStatus, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// How long a shell may take to print its `PATH` before it is killed.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The error returned when the `PATH` of a shell could not be captured.
+///
+/// This is returned by [`PathEnv::from_login_shell`] and
+/// [`PathEnv::from_shell_command`].
+///
+/// [`PathEnv::from_login_shell`]:   struct.PathEnv.html#method.from_login_shell
+/// [`PathEnv::from_shell_command`]: struct.PathEnv.html#method.from_shell_command
+#[derive(Debug)]
+pub enum ShellPathError {
+    /// The shell could not be run.
+    Spawn(io::Error),
+    /// The shell exited unsuccessfully without printing its `PATH`.
+    Status(ExitStatus),
+    /// The shell did not finish in time and was killed.
+    TimedOut,
+    /// The shell exited successfully without printing its `PATH`.
+    MissingOutput,
+}
+
+impl fmt::Display for ShellPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShellPathError::Spawn(error) => {
+                write!(f, "failed to run shell: {}", error)
+            }
+            ShellPathError::Status(status) => {
+                write!(f, "shell failed with {} before printing PATH", status)
+            }
+            ShellPathError::TimedOut => {
+                write!(f, "shell did not print PATH within {:?}", TIMEOUT)
+            }
+            ShellPathError::MissingOutput => {
+                f.write_str("shell exited without printing PATH")
+            }
+        }
+    }
+}
+
+impl Error for ShellPathError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ShellPathError::Spawn(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ShellPathError> for io::Error {
+    #[inline]
+    fn from(error: ShellPathError) -> Self {
+        let kind = match &error {
+            ShellPathError::Spawn(error) => error.kind(),
+            ShellPathError::TimedOut => io::ErrorKind::TimedOut,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+/// Returns a pair of markers that are unlikely to appear in any other output.
+fn markers() -> (String, String) {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos());
+    let id = format!("{}_{}", std::process::id(), nanos);
+    (format!("__path_env_start_{}__", id), format!("__path_env_end_{}__", id))
+}
+
+/// Returns the bytes between the last `start` and the `end` that follows it.
+fn between<'a>(output: &'a [u8], start: &str, end: &str) -> Option<&'a [u8]> {
+    let find = |haystack: &[u8], needle: &[u8]| {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    };
+
+    let start = output
+        .windows(start.len())
+        .rposition(|window| window == start.as_bytes())?
+        + start.len();
+    let len = find(&output[start..], end.as_bytes())?;
+    Some(&output[start..start + len])
+}
+
+impl PathEnv {
+    /// Returns the `PATH` that `shell` sets up for an interactive login
+    /// session, such as the one in the user's terminal.
+    ///
+    /// `shell` is usually the value of the `SHELL` environment variable. It
+    /// is run with `-l -i`, which is understood by `sh`, `bash`, `zsh`, `fish`
+    /// and others.
+    ///
+    /// Programs started from a desktop environment or a service manager often
+    /// get a much shorter `PATH` than the terminal, since they never run the
+    /// user's startup files. This runs them to resolve programs the same way
+    /// the user would.
+    ///
+    /// See [`from_shell_command`] for how the output is captured.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use path_env::PathEnv;
+    ///
+    /// let shell = std::env::var_os("SHELL");
+    /// let shell = shell.unwrap_or_else(|| "/bin/sh".into());
+    /// let path = PathEnv::from_login_shell(shell)?;
+    /// # Ok::<(), path_env::ShellPathError>(())
+    /// ```
+    ///
+    /// [`from_shell_command`]: #method.from_shell_command
+    pub fn from_login_shell<S: AsRef<OsStr>>(
+        shell: S,
+    ) -> Result<Self, ShellPathError> {
+        let mut command = Command::new(shell);
+        command.arg("-l").arg("-i");
+        Self::from_shell_command(command)
+    }
+
+    /// Returns the `PATH` printed by a shell run as `command`.
+    ///
+    /// `-c` and a script are appended to the arguments of `command`. The
+    /// script prints `PATH` between unique markers, so any other output from
+    /// startup files is ignored. Standard input is closed and standard error
+    /// is discarded. The shell is killed if it takes longer than 30 seconds.
+    ///
+    /// This allows running a shell with a custom environment or working
+    /// directory.
+    pub fn from_shell_command(
+        mut command: Command,
+    ) -> Result<Self, ShellPathError> {
+        let (start, end) = markers();
+
+        // Markers are split so that shells echoing the script, as with
+        // `set -v`, do not print them.
+        let (start_a, start_b) = start.split_at(start.len() / 2);
+        let (end_a, end_b) = end.split_at(end.len() / 2);
+        let script = format!(
+            "printf '%s%s%s%s%s' '{}' '{}' \"$PATH\" '{}' '{}'",
+            start_a, start_b, end_a, end_b
+        );
+
+        let mut child = command
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(ShellPathError::Spawn)?;
+
+        // Read on another thread so that the output can be checked while
+        // waiting for the shell to exit.
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            loop {
+                match stdout.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(len) => {
+                        if sender.send(buf[..len].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                    Err(error) => {
+                        if error.kind() != io::ErrorKind::Interrupted {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        let deadline = Instant::now() + TIMEOUT;
+        let mut output = Vec::new();
+        let mut status = None;
+        let mut eof = false;
+
+        // Background jobs started by startup files may keep the pipe open, so
+        // stop reading once the shell has exited and printed its `PATH`.
+        let status = loop {
+            if status.is_none() {
+                status = child.try_wait().map_err(ShellPathError::Spawn)?;
+            }
+            if let Some(status) = status {
+                if eof || between(&output, &start, &end).is_some() {
+                    break status;
+                }
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let status = child.wait().map_err(ShellPathError::Spawn)?;
+                if between(&output, &start, &end).is_some() {
+                    break status;
+                }
+                return Err(ShellPathError::TimedOut);
+            }
+
+            match receiver.recv_timeout(Duration::from_millis(10)) {
+                Ok(chunk) => output.extend(chunk),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    eof = true;
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+        };
+
+        match between(&output, &start, &end) {
+            Some(path) => Ok(OsStr::from_bytes(path).to_owned().into()),
+            None if !status.success() => Err(ShellPathError::Status(status)),
+            None => Err(ShellPathError::MissingOutput),
+        }
+    }
+}
@@ -1,13 +1,254 @@
 //! Unix-specific definitions.
 
-use std::{ffi::OsString, io, os::unix::ffi::OsStringExt, process::Command};
+use std::{
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt, io,
+    iter::FusedIterator,
+    os::unix::ffi::{OsStrExt, OsStringExt},
+    process::{Command, ExitStatus},
+};
 
-/// Returns the `PATH` returned by running `getconf PATH`.
+/// Locations tried for the `getconf` binary, in order.
+///
+/// Absolute paths come first, so that a broken `PATH` does not prevent finding
+/// the default one.
+const GETCONF_PROGRAMS: &[&str] =
+    &["/usr/bin/getconf", "/bin/getconf", "getconf"];
+
+/// The error returned when the default `PATH` could not be determined.
+#[derive(Debug)]
+pub enum GetconfError {
+    /// The `getconf` binary could not be run.
+    Spawn(io::Error),
+    /// `getconf` exited with an unsuccessful status.
+    Status(ExitStatus),
+    /// `getconf` succeeded but printed nothing.
+    Empty,
+}
+
+impl fmt::Display for GetconfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("could not get the default PATH: ")?;
+        match self {
+            GetconfError::Spawn(error) => {
+                write!(f, "failed to run `getconf PATH`: {}", error)
+            }
+            GetconfError::Status(status) => {
+                write!(f, "`getconf PATH` failed with {}", status)
+            }
+            GetconfError::Empty => {
+                f.write_str("`getconf PATH` printed nothing")
+            }
+        }
+    }
+}
+
+impl Error for GetconfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GetconfError::Spawn(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<GetconfError> for io::Error {
+    #[inline]
+    fn from(error: GetconfError) -> Self {
+        let kind = match &error {
+            GetconfError::Spawn(error) => error.kind(),
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+/// Returns the default `PATH` reported by the system.
 ///
 /// This is usually used to get the default `PATH` on POSIX-compliant systems.
-pub fn getconf() -> io::Result<OsString> {
-    Command::new("getconf")
-        .arg("PATH")
-        .output()
-        .map(|output| OsString::from_vec(output.stdout))
+/// It is queried through [`confstr_path`], falling back to running
+/// `getconf PATH` if that is unavailable. Surrounding whitespace is removed
+/// from the output of `getconf`.
+///
+/// [`confstr_path`]: fn.confstr_path.html
+pub fn getconf() -> Result<OsString, GetconfError> {
+    if let Some(path) = confstr_path() {
+        return Ok(path);
+    }
+
+    let mut spawn_error = None;
+    for program in GETCONF_PROGRAMS {
+        let output = match Command::new(program).arg("PATH").output() {
+            Ok(output) => output,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                spawn_error.get_or_insert(error);
+                continue;
+            }
+            Err(error) => return Err(GetconfError::Spawn(error)),
+        };
+
+        if !output.status.success() {
+            return Err(GetconfError::Status(output.status));
+        }
+
+        let mut path = output.stdout;
+        let end = path
+            .iter()
+            .rposition(|b| !b.is_ascii_whitespace())
+            .map_or(0, |i| i + 1);
+        let start = path[..end]
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(end);
+        path.truncate(end);
+        path.drain(..start);
+
+        if path.is_empty() {
+            return Err(GetconfError::Empty);
+        }
+        return Ok(OsString::from_vec(path));
+    }
+
+    Err(GetconfError::Spawn(spawn_error.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "getconf not found")
+    })))
+}
+
+/// Returns the default `PATH` from `confstr(_CS_PATH)`, or `None` if the
+/// platform does not provide it.
+///
+/// Unlike [`getconf`], this does not spawn a process.
+///
+/// [`getconf`]: fn.getconf.html
+pub fn confstr_path() -> Option<OsString> {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "redox",
+    ))]
+    {
+        use std::ptr;
+
+        // SAFETY: A null buffer of length 0 only queries the required size.
+        let len = unsafe { libc::confstr(libc::_CS_PATH, ptr::null_mut(), 0) };
+        if len == 0 {
+            return None;
+        }
+
+        let mut buf = vec![0u8; len];
+
+        // SAFETY: `buf` is valid for writes of `len` bytes.
+        let written = unsafe {
+            libc::confstr(libc::_CS_PATH, buf.as_mut_ptr().cast(), len)
+        };
+
+        // `written` includes the trailing nul byte.
+        if written == 0 || written > len {
+            return None;
+        }
+        buf.truncate(written - 1);
+
+        if buf.is_empty() {
+            None
+        } else {
+            Some(OsString::from_vec(buf))
+        }
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "redox",
+    )))]
+    {
+        None
+    }
+}
+
+/// Returns an iterator over the `NAME=value` pairs of an environment block.
+///
+/// An environment block is a list of entries that each end in a nul byte, as
+/// found in `/proc/<pid>/environ` or printed by `env -0`. The final nul byte
+/// is optional, and entries without `=` are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use path_env::os::unix;
+/// use std::ffi::OsStr;
+///
+/// let block = b"HOME=/home/me\0PATH=/usr/bin:/bin\0";
+/// let vars = unix::environ_vars(block).collect::<Vec<_>>();
+///
+/// let home = (OsStr::new("HOME"), OsStr::new("/home/me"));
+/// let path = (OsStr::new("PATH"), OsStr::new("/usr/bin:/bin"));
+/// assert_eq!(vars, [home, path]);
+/// ```
+#[inline]
+pub fn environ_vars(block: &[u8]) -> EnvironVars<'_> {
+    EnvironVars { block }
 }
+
+/// Returns the value of the first variable called `name` in an environment
+/// block.
+///
+/// See [`environ_vars`] for the format of `block`.
+///
+/// [`environ_vars`]: fn.environ_vars.html
+pub fn environ_var<'a, N>(block: &'a [u8], name: &N) -> Option<&'a OsStr>
+where
+    N: AsRef<OsStr> + ?Sized,
+{
+    let name = name.as_ref();
+    environ_vars(block).find(|(n, _)| *n == name).map(|(_, value)| value)
+}
+
+/// An iterator over the variables of an environment block.
+///
+/// This is returned by [`environ_vars`].
+///
+/// [`environ_vars`]: fn.environ_vars.html
+#[derive(Clone, Debug)]
+pub struct EnvironVars<'a> {
+    block: &'a [u8],
+}
+
+impl<'a> Iterator for EnvironVars<'a> {
+    type Item = (&'a OsStr, &'a OsStr);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.block.is_empty() {
+            let end = self
+                .block
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(self.block.len());
+            let entry = &self.block[..end];
+            self.block = self.block.get(end + 1..).unwrap_or_default();
+
+            // Like `std::env`, allow names that start with `=`.
+            let eq = match entry.iter().skip(1).position(|&b| b == b'=') {
+                Some(eq) => eq + 1,
+                None => continue,
+            };
+            return Some((
+                OsStr::from_bytes(&entry[..eq]),
+                OsStr::from_bytes(&entry[eq + 1..]),
+            ));
+        }
+        None
+    }
+}
+
+impl FusedIterator for EnvironVars<'_> {}
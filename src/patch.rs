@@ -0,0 +1,277 @@
+use crate::PathEnv;
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+/// A single operation of a [`PathPatch`].
+///
+/// Every operation affects a single entry, and applying it again right after
+/// has no further effect.
+///
+/// [`PathPatch`]: struct.PathPatch.html
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PatchOp {
+    /// Moves or adds the entry to the front.
+    Prepend(PathBuf),
+    /// Moves or adds the entry to the back.
+    Append(PathBuf),
+    /// Removes every occurrence of the entry.
+    Remove(PathBuf),
+    /// Moves or adds `path` right before the first occurrence of `anchor`.
+    ///
+    /// Nothing happens if `anchor` is not present.
+    InsertBefore {
+        /// The entry to insert.
+        path: PathBuf,
+        /// The entry to insert before.
+        anchor: PathBuf,
+    },
+    /// Moves or adds `path` right after the first occurrence of `anchor`.
+    ///
+    /// Nothing happens if `anchor` is not present.
+    InsertAfter {
+        /// The entry to insert.
+        path: PathBuf,
+        /// The entry to insert after.
+        anchor: PathBuf,
+    },
+    /// Moves the entry to the front if it is present.
+    MoveToFront(PathBuf),
+    /// Removes every occurrence of `path`, then inserts each of `entries` at
+    /// its index in ascending order.
+    ///
+    /// This is used by inverse patches to put an entry back where it was,
+    /// written exactly as it was, such as with a trailing separator. Indices
+    /// past the end insert at the back.
+    Reposition {
+        /// The entry to reposition.
+        path: PathBuf,
+        /// The indices where the entry should end up, with the text to
+        /// insert at each.
+        entries: Vec<(usize, OsString)>,
+    },
+}
+
+impl PatchOp {
+    /// Returns the entry that this operation adds, moves or removes.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        match self {
+            PatchOp::Prepend(path)
+            | PatchOp::Append(path)
+            | PatchOp::Remove(path)
+            | PatchOp::MoveToFront(path)
+            | PatchOp::InsertBefore { path, .. }
+            | PatchOp::InsertAfter { path, .. }
+            | PatchOp::Reposition { path, .. } => path,
+        }
+    }
+
+    fn apply(&self, entries: &mut Vec<PathBuf>) {
+        let path = self.path();
+        let find = |entries: &[PathBuf], target: &Path| {
+            entries.iter().position(|entry| entry == target)
+        };
+
+        match self {
+            PatchOp::MoveToFront(_) if find(entries, path).is_none() => return,
+            PatchOp::InsertBefore { anchor, .. }
+            | PatchOp::InsertAfter { anchor, .. }
+                if anchor.as_path() == path
+                    || find(entries, anchor).is_none() =>
+            {
+                return
+            }
+            _ => {}
+        }
+
+        entries.retain(|entry| entry != path);
+
+        match self {
+            PatchOp::Prepend(_) | PatchOp::MoveToFront(_) => {
+                entries.insert(0, path.to_owned());
+            }
+            PatchOp::Append(_) => entries.push(path.to_owned()),
+            PatchOp::Remove(_) => {}
+            PatchOp::InsertBefore { anchor, .. } => {
+                let index = find(entries, anchor).unwrap_or(0);
+                entries.insert(index, path.to_owned());
+            }
+            PatchOp::InsertAfter { anchor, .. } => {
+                let index = find(entries, anchor).map_or(0, |i| i + 1);
+                entries.insert(index, path.to_owned());
+            }
+            PatchOp::Reposition {
+                entries: originals, ..
+            } => {
+                let mut originals = originals.clone();
+                originals.sort_by_key(|&(index, _)| index);
+                for (index, original) in originals {
+                    let index = index.min(entries.len());
+                    entries.insert(index, original.into());
+                }
+            }
+        }
+    }
+}
+
+/// An ordered list of operations to apply to a [`PathEnv`].
+///
+/// Applying a patch returns its inverse, which undoes exactly what the patch
+/// did. This makes it suitable for activation hooks that must be undone
+/// later.
+///
+/// # Examples
+///
+/// ```
+/// use path_env::{PathEnv, PathPatch};
+///
+/// let original: PathEnv = vec!["/usr/bin", "/bin"].into_iter().collect();
+///
+/// let mut patch = PathPatch::new();
+/// patch
+///     .prepend("/project/bin")
+///     .insert_after("/project/tools", "/usr/bin");
+///
+/// let mut path = original.clone();
+/// let undo = patch.apply(&mut path);
+///
+/// let expected: PathEnv =
+///     vec!["/project/bin", "/usr/bin", "/project/tools", "/bin"]
+///         .into_iter()
+///         .collect();
+/// assert_eq!(path, expected);
+///
+/// // Applying the patch again does nothing.
+/// patch.apply(&mut path);
+/// assert_eq!(path, expected);
+///
+/// undo.apply(&mut path);
+/// assert_eq!(path, original);
+/// ```
+///
+/// [`PathEnv`]: struct.PathEnv.html
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PathPatch {
+    ops: Vec<PatchOp>,
+}
+
+impl From<Vec<PatchOp>> for PathPatch {
+    #[inline]
+    fn from(ops: Vec<PatchOp>) -> Self {
+        Self { ops }
+    }
+}
+
+impl PathPatch {
+    /// Creates an empty patch.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the operations of `self` in the order they are applied.
+    #[inline]
+    pub fn ops(&self) -> &[PatchOp] {
+        &self.ops
+    }
+
+    /// Returns `true` if `self` has no operations.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Adds `op` to the end of `self`.
+    #[inline]
+    pub fn push(&mut self, op: PatchOp) -> &mut Self {
+        self.ops.push(op);
+        self
+    }
+
+    /// Adds a [`PatchOp::Prepend`](enum.PatchOp.html#variant.Prepend).
+    #[inline]
+    pub fn prepend<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.push(PatchOp::Prepend(path.into()))
+    }
+
+    /// Adds a [`PatchOp::Append`](enum.PatchOp.html#variant.Append).
+    #[inline]
+    pub fn append<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.push(PatchOp::Append(path.into()))
+    }
+
+    /// Adds a [`PatchOp::Remove`](enum.PatchOp.html#variant.Remove).
+    #[inline]
+    pub fn remove<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.push(PatchOp::Remove(path.into()))
+    }
+
+    /// Adds a [`PatchOp::InsertBefore`].
+    ///
+    /// [`PatchOp::InsertBefore`]: enum.PatchOp.html#variant.InsertBefore
+    #[inline]
+    pub fn insert_before<P, A>(&mut self, path: P, anchor: A) -> &mut Self
+    where
+        P: Into<PathBuf>,
+        A: Into<PathBuf>,
+    {
+        self.push(PatchOp::InsertBefore {
+            path: path.into(),
+            anchor: anchor.into(),
+        })
+    }
+
+    /// Adds a [`PatchOp::InsertAfter`](enum.PatchOp.html#variant.InsertAfter).
+    #[inline]
+    pub fn insert_after<P, A>(&mut self, path: P, anchor: A) -> &mut Self
+    where
+        P: Into<PathBuf>,
+        A: Into<PathBuf>,
+    {
+        self.push(PatchOp::InsertAfter {
+            path: path.into(),
+            anchor: anchor.into(),
+        })
+    }
+
+    /// Adds a [`PatchOp::MoveToFront`](enum.PatchOp.html#variant.MoveToFront).
+    #[inline]
+    pub fn move_to_front<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.push(PatchOp::MoveToFront(path.into()))
+    }
+
+    /// Applies the operations of `self` to `path` in order, and returns the
+    /// patch that undoes them.
+    ///
+    /// The inverse records where each affected entry was before, so applying
+    /// it right after restores `path` exactly. Entries that the patch did not
+    /// touch are left alone by the inverse, even if they changed in between.
+    pub fn apply(&self, path: &mut PathEnv) -> PathPatch {
+        let mut entries =
+            path.iter().map(Path::to_path_buf).collect::<Vec<_>>();
+        let mut inverse = Vec::with_capacity(self.ops.len());
+
+        for op in &self.ops {
+            let originals = entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| *entry == op.path())
+                .map(|(i, entry)| (i, entry.clone().into_os_string()))
+                .collect();
+
+            op.apply(&mut entries);
+
+            inverse.push(PatchOp::Reposition {
+                path: op.path().to_owned(),
+                entries: originals,
+            });
+        }
+
+        *path = entries.into_iter().collect();
+
+        inverse.reverse();
+        inverse.into()
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{lock, PathEnv};
+use std::{env, fmt};
+
+/// Exclusive access to the `PATH` environment variable of the current process.
+///
+/// Every function in this crate that reads or writes the environment, such as
+/// [`PathEnv::from_var`] and [`PathEnv::set_var`], does so while holding a
+/// crate-wide lock. A `ProcessPath` holds that lock for as long as it is alive,
+/// which makes a sequence of reads and writes appear atomic to other threads
+/// that go through this crate.
+///
+/// The lock is reentrant, so the current thread may keep using the rest of
+/// this crate while it holds a `ProcessPath`.
+///
+/// # Safety Boundary
+///
+/// The lock only covers accesses made through this crate. Calling
+/// [`env::set_var`] or [`env::remove_var`] directly, or `setenv`/`getenv`
+/// from C code, while other threads use the environment remains a data race
+/// on some platforms. Programs that change `PATH` at runtime should do so
+/// exclusively through this crate.
+///
+/// # Examples
+///
+/// Prepending a directory without racing other threads doing the same:
+///
+/// ```
+/// use path_env::ProcessPath;
+///
+/// ProcessPath::update(|path| {
+///     if !path.contains("/opt/mytool/bin") {
+///         path.push_back("/opt/mytool/bin");
+///     }
+/// });
+/// ```
+///
+/// [`PathEnv::from_var`]: struct.PathEnv.html#method.from_var
+/// [`PathEnv::set_var`]:  struct.PathEnv.html#method.set_var
+/// [`env::set_var`]:      https://doc.rust-lang.org/std/env/fn.set_var.html
+/// [`env::remove_var`]:   https://doc.rust-lang.org/std/env/fn.remove_var.html
+pub struct ProcessPath {
+    _lock: lock::ReentrantLockGuard<'static>,
+}
+
+impl fmt::Debug for ProcessPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ProcessPath").field(&self.get()).finish()
+    }
+}
+
+impl ProcessPath {
+    /// Acquires exclusive access to `PATH`, blocking until other threads
+    /// release it.
+    #[inline]
+    pub fn lock() -> Self {
+        Self {
+            _lock: lock::ENV.lock(),
+        }
+    }
+
+    /// Atomically replaces `PATH` with the result of calling `f` on it.
+    ///
+    /// If `PATH` is not set, `f` is given an empty [`PathEnv`], and `PATH`
+    /// stays unset unless `f` adds to it. Returns the result of `f`.
+    ///
+    /// [`PathEnv`]: struct.PathEnv.html
+    pub fn update<F, R>(f: F) -> R
+    where
+        F: FnOnce(&mut PathEnv) -> R,
+    {
+        let lock = Self::lock();
+        let previous = lock.get();
+
+        let mut path = previous.clone().unwrap_or_else(PathEnv::empty);
+        let result = f(&mut path);
+
+        match previous {
+            Some(previous) if previous.as_os_str() == path.as_os_str() => {}
+            None if path.is_empty() => {}
+            _ => lock.set(&path),
+        }
+
+        result
+    }
+
+    /// Returns the current `PATH`, or `None` if it is not set.
+    #[inline]
+    pub fn get(&self) -> Option<PathEnv> {
+        env::var_os("PATH").map(PathEnv::from)
+    }
+
+    /// Sets `PATH` to `path`.
+    #[inline]
+    pub fn set(&self, path: &PathEnv) {
+        env::set_var("PATH", path);
+    }
+
+    /// Removes `PATH` from the environment.
+    #[inline]
+    pub fn remove(&self) {
+        env::remove_var("PATH");
+    }
+}
@@ -0,0 +1,310 @@
+//! Named `PATH` profiles loaded from a configuration file.
+//!
+//! A profile lists entries and may extend another profile, whose entries come
+//! after its own. Entries may use `~` and variable references, which are
+//! expanded as in [`PathEnv::expand`] when the profile is resolved.
+//!
+//! Profiles are written in TOML, one table per profile:
+//!
+//! ```toml
+//! [minimal]
+//! entries = ["/usr/bin", "/bin"]
+//!
+//! [build]
+//! extends = "minimal"
+//! entries = ["~/.cargo/bin", "$HOME/.local/bin"]
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(unix)] {
+//! use path_env::profile::Profiles;
+//!
+//! let profiles = Profiles::parse(r#"
+//!     [minimal]
+//!     entries = ["/usr/bin", "/bin"]
+//!
+//!     [build]
+//!     extends = "minimal"
+//!     entries = ["$TOOLS/bin"]
+//! "#)?;
+//!
+//! let path = profiles.resolve_with("build", |name| match name.to_str()? {
+//!     "TOOLS" => Some("/opt/tools".into()),
+//!     _ => None,
+//! })?;
+//!
+//! assert_eq!(path.as_os_str(), "/opt/tools/bin:/usr/bin:/bin");
+//! # }
+//! # Ok::<(), path_env::profile::ProfileError>(())
+//! ```
+//!
+//! [`PathEnv::expand`]: ../struct.PathEnv.html#method.expand
+
+use crate::{lock, PathEnv};
+use ::serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The environment variable that overrides the location of the profiles
+/// file.
+pub const CONFIG_VAR: &str = "PATH_ENV_PROFILES";
+
+/// A named list of entries, optionally extending another profile.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(default)]
+    extends: Option<String>,
+    #[serde(default)]
+    entries: Vec<String>,
+}
+
+impl Profile {
+    /// Creates a profile with `entries`, which may use `~` and variable
+    /// references.
+    pub fn new<I, S>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            extends: None,
+            entries: entries.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns `self` extending the profile called `name`.
+    #[inline]
+    pub fn with_extends<N: Into<String>>(mut self, name: N) -> Self {
+        self.extends = Some(name.into());
+        self
+    }
+
+    /// Returns the name of the profile that `self` extends.
+    #[inline]
+    pub fn extends(&self) -> Option<&str> {
+        self.extends.as_deref()
+    }
+
+    /// Returns the entries of `self` before expansion, not including those of
+    /// the profile it extends.
+    #[inline]
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+}
+
+/// A set of named [`Profile`]s.
+///
+/// See the [module documentation](index.html) for more info.
+///
+/// [`Profile`]: struct.Profile.html
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Profiles {
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    /// Creates an empty set of profiles.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses profiles from TOML.
+    pub fn parse(text: &str) -> Result<Self, ProfileError> {
+        toml::from_str(text)
+            .map_err(|error| ProfileError::Parse(error.to_string()))
+    }
+
+    /// Reads profiles from the TOML file at `file`.
+    pub fn read<P: AsRef<Path>>(file: P) -> Result<Self, ProfileError> {
+        let text = fs::read_to_string(file).map_err(ProfileError::Io)?;
+        Self::parse(&text)
+    }
+
+    /// Returns the location of the profiles file of the current user.
+    ///
+    /// This is the value of [`CONFIG_VAR`] if set. Otherwise, it is
+    /// `path_env/profiles.toml` in `$XDG_CONFIG_HOME` or `~/.config` on
+    /// Unix-like systems, and in `%APPDATA%` on Windows.
+    ///
+    /// [`CONFIG_VAR`]: constant.CONFIG_VAR.html
+    pub fn default_file() -> Option<PathBuf> {
+        if let Some(file) = lock::var_os(CONFIG_VAR) {
+            return Some(file.into());
+        }
+
+        #[cfg(windows)]
+        let config_home = lock::var_os("APPDATA").map(PathBuf::from);
+
+        #[cfg(not(windows))]
+        let config_home = lock::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| Path::new(dir).is_absolute())
+            .map(PathBuf::from)
+            .or_else(|| {
+                lock::var_os("HOME")
+                    .map(|home| Path::new(&home).join(".config"))
+            });
+
+        config_home.map(|dir| dir.join("path_env").join("profiles.toml"))
+    }
+
+    /// Reads profiles from [`default_file`](#method.default_file).
+    pub fn from_default_file() -> Result<Self, ProfileError> {
+        let file = Self::default_file().ok_or_else(|| {
+            ProfileError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                "could not locate the profiles file",
+            ))
+        })?;
+        Self::read(file)
+    }
+
+    /// Returns the profile called `name`.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /// Returns an iterator over the names of all profiles, in sorted order.
+    #[inline]
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Adds `profile` as `name`, returning the profile it replaced.
+    #[inline]
+    pub fn insert<N: Into<String>>(
+        &mut self,
+        name: N,
+        profile: Profile,
+    ) -> Option<Profile> {
+        self.profiles.insert(name.into(), profile)
+    }
+
+    /// Returns the entries of the profile called `name`, expanded using the
+    /// current environment.
+    ///
+    /// See [`resolve_with`](#method.resolve_with) for more info.
+    #[inline]
+    pub fn resolve(&self, name: &str) -> Result<PathEnv, ProfileError> {
+        self.resolve_with(name, |name| lock::var_os(name))
+    }
+
+    /// Returns the entries of the profile called `name`, followed by those of
+    /// the profiles it extends, expanding references by calling `lookup`.
+    ///
+    /// Entries that appear more than once are only kept the first time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` or a profile it extends does not exist, or
+    /// if a profile extends itself through a cycle.
+    pub fn resolve_with<F>(
+        &self,
+        name: &str,
+        mut lookup: F,
+    ) -> Result<PathEnv, ProfileError>
+    where
+        F: FnMut(&OsStr) -> Option<OsString>,
+    {
+        let mut chain = Vec::<&str>::new();
+        let mut next = Some(name);
+
+        while let Some(name) = next {
+            if chain.contains(&name) {
+                let mut cycle = chain
+                    .iter()
+                    .skip_while(|&&n| n != name)
+                    .map(|&n| n.to_owned())
+                    .collect::<Vec<_>>();
+                cycle.push(name.to_owned());
+                return Err(ProfileError::Cycle(cycle));
+            }
+
+            let profile = self
+                .get(name)
+                .ok_or_else(|| ProfileError::NotFound(name.to_owned()))?;
+            chain.push(name);
+            next = profile.extends();
+        }
+
+        let mut path = PathEnv::empty();
+        for name in chain {
+            for entry in &self.profiles[name].entries {
+                for entry in PathEnv::expand_with(entry, &mut lookup).iter() {
+                    if !path.contains(entry) {
+                        path.push_back(entry);
+                    }
+                }
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// The error returned when a profile could not be loaded or resolved.
+#[derive(Debug)]
+pub enum ProfileError {
+    /// The profiles file could not be read.
+    Io(io::Error),
+    /// The profiles file is not valid.
+    Parse(String),
+    /// There is no profile with the given name.
+    NotFound(String),
+    /// Profiles extend each other in a cycle, given in order of extension and
+    /// ending with the first profile again.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProfileError::Io(error) => {
+                write!(f, "failed to read profiles: {}", error)
+            }
+            ProfileError::Parse(message) => {
+                write!(f, "invalid profiles: {}", message)
+            }
+            ProfileError::NotFound(name) => {
+                write!(f, "no profile named {:?}", name)
+            }
+            ProfileError::Cycle(cycle) => {
+                write!(f, "profiles extend each other: {}", cycle.join(" -> "))
+            }
+        }
+    }
+}
+
+impl Error for ProfileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProfileError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl PathEnv {
+    /// Resolves the profile called `name` from the current user's profiles
+    /// file.
+    ///
+    /// See [`profile`](profile/index.html) for the file format and
+    /// [`Profiles::default_file`] for where it is found.
+    ///
+    /// [`Profiles::default_file`]: profile/struct.Profiles.html#method.default_file
+    #[cfg_attr(feature = "_doc-cfg", doc(cfg(feature = "profiles")))]
+    #[inline]
+    pub fn from_profile(name: &str) -> Result<Self, ProfileError> {
+        Profiles::from_default_file()?.resolve(name)
+    }
+}
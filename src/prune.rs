@@ -0,0 +1,299 @@
+use crate::{separator, vars, PathEnv};
+use std::{
+    ffi::OsString,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    slice,
+};
+
+/// Which entries [`PathEnv::prune`] removes.
+///
+/// By default, entries are removed for every [`PruneReason`]. Relative
+/// entries are always kept, since whether they exist depends on the current
+/// directory.
+///
+/// # Examples
+///
+/// Keep entries that exist but cannot be read, such as those only readable
+/// by another user:
+///
+/// ```
+/// use path_env::PrunePolicy;
+///
+/// let policy = PrunePolicy::new().with_unreadable(false);
+/// ```
+///
+/// [`PathEnv::prune`]: struct.PathEnv.html#method.prune
+/// [`PruneReason`]:    enum.PruneReason.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PrunePolicy {
+    missing: bool,
+    not_dir: bool,
+    unreadable: bool,
+}
+
+impl Default for PrunePolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrunePolicy {
+    /// Creates a policy that removes entries for every reason.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            missing: true,
+            not_dir: true,
+            unreadable: true,
+        }
+    }
+
+    /// Returns `self` with whether to remove entries that do not exist,
+    /// including broken symbolic links.
+    #[inline]
+    pub const fn with_missing(mut self, remove: bool) -> Self {
+        self.missing = remove;
+        self
+    }
+
+    /// Returns `self` with whether to remove entries that are not
+    /// directories.
+    #[inline]
+    pub const fn with_not_dir(mut self, remove: bool) -> Self {
+        self.not_dir = remove;
+        self
+    }
+
+    /// Returns `self` with whether to remove directories that cannot be
+    /// read.
+    #[inline]
+    pub const fn with_unreadable(mut self, remove: bool) -> Self {
+        self.unreadable = remove;
+        self
+    }
+
+    /// Returns `true` if `self` removes entries for `reason`.
+    #[inline]
+    pub fn removes(&self, reason: PruneReason) -> bool {
+        match reason {
+            PruneReason::Missing => self.missing,
+            PruneReason::NotDir => self.not_dir,
+            PruneReason::Unreadable(_) => self.unreadable,
+        }
+    }
+}
+
+/// Why [`PathEnv::prune`] removed an entry.
+///
+/// [`PathEnv::prune`]: struct.PathEnv.html#method.prune
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PruneReason {
+    /// The entry does not exist, or is a broken symbolic link.
+    Missing,
+    /// The entry exists but is not a directory.
+    NotDir,
+    /// The entry or its contents could not be read, for the given reason.
+    Unreadable(io::ErrorKind),
+}
+
+impl fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PruneReason::Missing => f.write_str("does not exist"),
+            PruneReason::NotDir => f.write_str("is not a directory"),
+            PruneReason::Unreadable(kind) => {
+                write!(f, "cannot be read: {}", io::Error::from(kind))
+            }
+        }
+    }
+}
+
+/// An entry removed by [`PathEnv::prune`].
+///
+/// [`PathEnv::prune`]: struct.PathEnv.html#method.prune
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PrunedEntry {
+    index: usize,
+    path: PathBuf,
+    reason: PruneReason,
+}
+
+impl PrunedEntry {
+    /// Returns the index of the entry before pruning, counting empty entries
+    /// as in [`AuditFinding::index`].
+    ///
+    /// [`AuditFinding::index`]: struct.AuditFinding.html#method.index
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the entry that was removed.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns why the entry was removed.
+    #[inline]
+    pub fn reason(&self) -> PruneReason {
+        self.reason
+    }
+}
+
+impl fmt::Display for PrunedEntry {
+    /// Writes a summary such as `-/opt/old/bin (does not exist)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "-{} ({})", self.path.display(), self.reason)
+    }
+}
+
+/// The entries removed by [`PathEnv::prune`], in their original order.
+///
+/// [`PathEnv::prune`]: struct.PathEnv.html#method.prune
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    removed: Vec<PrunedEntry>,
+}
+
+impl PruneReport {
+    /// Returns `true` if no entries were removed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+
+    /// Returns the number of entries removed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.removed.len()
+    }
+
+    /// Returns an iterator over the entries removed.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, PrunedEntry> {
+        self.removed.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PruneReport {
+    type Item = &'a PrunedEntry;
+    type IntoIter = slice::Iter<'a, PrunedEntry>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for PruneReport {
+    /// Writes the entries removed separated by commas, such as
+    /// `-/opt/old/bin (does not exist), -/etc/passwd (is not a directory)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, entry) in self.removed.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            entry.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns why `path` cannot be searched for programs, if it cannot.
+fn check(path: &Path) -> Option<PruneReason> {
+    // Symbolic links are followed, so links to directories are kept.
+    let meta = match fs::metadata(path) {
+        Ok(meta) => meta,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Some(PruneReason::Missing)
+        }
+        Err(error) => return Some(PruneReason::Unreadable(error.kind())),
+    };
+
+    if !meta.is_dir() {
+        return Some(PruneReason::NotDir);
+    }
+
+    match fs::read_dir(path) {
+        Ok(_) => None,
+        Err(error) => Some(PruneReason::Unreadable(error.kind())),
+    }
+}
+
+impl PathEnv {
+    /// Removes entries that cannot contain programs, and returns which were
+    /// removed and why.
+    ///
+    /// Entries are removed if they do not exist, are not directories, or
+    /// cannot be read, as allowed by `policy`. Symbolic links are followed,
+    /// so links to directories are kept. Relative and empty entries are
+    /// always kept.
+    ///
+    /// Stale entries, such as those left behind by uninstalled toolchains,
+    /// slow down every lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use path_env::{PathEnv, PrunePolicy, PruneReason};
+    ///
+    /// let dir = std::env::temp_dir();
+    /// let missing = dir.join("path_env-prune-example-missing");
+    ///
+    /// let mut path: PathEnv = vec![&missing, &dir].into_iter().collect();
+    /// let report = path.prune(PrunePolicy::new());
+    ///
+    /// assert_eq!(path.iter().collect::<Vec<_>>(), [dir.as_path()]);
+    /// assert_eq!(report.len(), 1);
+    ///
+    /// let removed = report.iter().next().unwrap();
+    /// assert_eq!(removed.path(), missing);
+    /// assert_eq!(removed.reason(), PruneReason::Missing);
+    /// ```
+    pub fn prune(&mut self, policy: PrunePolicy) -> PruneReport {
+        let mut removed = Vec::new();
+        let mut kept = OsString::with_capacity(self.path.len());
+        let mut kept_len = 0;
+
+        // Going through the raw entries keeps empty ones, which mean the
+        // current directory.
+        for (index, path) in vars::PATH.entries(self.as_os_str()).enumerate() {
+            let reason = if path.is_absolute() {
+                check(path)
+            } else {
+                None
+            };
+            match reason {
+                Some(reason) if policy.removes(reason) => {
+                    removed.push(PrunedEntry {
+                        index,
+                        path: path.to_owned(),
+                        reason,
+                    });
+                }
+                _ => {
+                    if kept_len != 0 {
+                        kept.push(separator::OS_STR);
+                    }
+                    kept.push(path);
+                    kept_len += 1;
+                }
+            }
+        }
+
+        // An empty value has no entries, so a lone empty entry is written as
+        // a separator between two.
+        if kept_len == 1 && kept.is_empty() {
+            kept.push(separator::OS_STR);
+        }
+
+        if !removed.is_empty() {
+            *self = kept.into();
+        }
+
+        PruneReport { removed }
+    }
+}
@@ -0,0 +1,204 @@
+//! Serialization of `PathEnv` values.
+//!
+//! Human-readable formats get a list of entries. Entries that are not valid
+//! Unicode are written as a list of integers: bytes on Unix and UTF-16 code
+//! units on Windows. Binary formats get the whole value as bytes, which on
+//! Windows are UTF-16 code units in little-endian order.
+//!
+//! Either form may be read back, as well as a single string of entries
+//! joined by the separator.
+
+use crate::PathEnv;
+use ::serde::{
+    de::{self, Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, SerializeSeq, Serializer},
+};
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+    path::Path,
+};
+
+use imp::{from_bytes, from_units, to_bytes, units, Unit};
+
+#[cfg(unix)]
+mod imp {
+    use super::*;
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    /// A code unit of the platform string encoding.
+    pub type Unit = u8;
+
+    pub fn units(s: &OsStr) -> Vec<Unit> {
+        s.as_bytes().to_vec()
+    }
+
+    pub fn from_units(units: Vec<Unit>) -> OsString {
+        OsString::from_vec(units)
+    }
+
+    pub fn to_bytes(s: &OsStr) -> Vec<u8> {
+        units(s)
+    }
+
+    pub fn from_bytes<E: de::Error>(bytes: Vec<u8>) -> Result<OsString, E> {
+        Ok(from_units(bytes))
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::*;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
+    /// A code unit of the platform string encoding.
+    pub type Unit = u16;
+
+    pub fn units(s: &OsStr) -> Vec<Unit> {
+        s.encode_wide().collect()
+    }
+
+    pub fn from_units(units: Vec<Unit>) -> OsString {
+        OsString::from_wide(&units)
+    }
+
+    pub fn to_bytes(s: &OsStr) -> Vec<u8> {
+        units(s).into_iter().flat_map(u16::to_le_bytes).collect()
+    }
+
+    pub fn from_bytes<E: de::Error>(bytes: Vec<u8>) -> Result<OsString, E> {
+        if bytes.len() % 2 != 0 {
+            return Err(E::invalid_length(
+                bytes.len(),
+                &"an even number of bytes",
+            ));
+        }
+        let units = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        Ok(from_units(units))
+    }
+}
+
+/// An entry that is serialized as a string if possible.
+struct SerEntry<'a>(&'a Path);
+
+impl Serialize for SerEntry<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self.0.to_str() {
+            Some(s) => serializer.serialize_str(s),
+            None => serializer.collect_seq(units(self.0.as_os_str())),
+        }
+    }
+}
+
+impl Serialize for PathEnv {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let mut seq = serializer.serialize_seq(Some(self.len()))?;
+            for entry in self.iter() {
+                seq.serialize_element(&SerEntry(entry))?;
+            }
+            seq.end()
+        } else {
+            serializer.serialize_bytes(&to_bytes(self.as_os_str()))
+        }
+    }
+}
+
+/// An entry given as a string, bytes or list of code units.
+struct DeEntry(OsString);
+
+impl<'de> Deserialize<'de> for DeEntry {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = DeEntry;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a path string or a list of code units")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<DeEntry, E> {
+                Ok(DeEntry(v.into()))
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<DeEntry, E> {
+                from_bytes(v.to_vec()).map(DeEntry)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<DeEntry, A::Error> {
+                let mut units =
+                    Vec::<Unit>::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(unit) = seq.next_element()? {
+                    units.push(unit);
+                }
+                Ok(DeEntry(from_units(units)))
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+struct PathEnvVisitor;
+
+impl<'de> Visitor<'de> for PathEnvVisitor {
+    type Value = PathEnv;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of paths or a separator-joined string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<PathEnv, E> {
+        Ok(PathEnv::from(v.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<PathEnv, E> {
+        Ok(PathEnv::from(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<PathEnv, E> {
+        from_bytes(v.to_vec()).map(PathEnv::from)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<PathEnv, E> {
+        from_bytes(v).map(PathEnv::from)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<PathEnv, A::Error> {
+        let mut path = PathEnv::empty();
+        while let Some(DeEntry(entry)) = seq.next_element()? {
+            path.push_back(entry);
+        }
+        Ok(path)
+    }
+}
+
+impl<'de> Deserialize<'de> for PathEnv {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PathEnvVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(PathEnvVisitor)
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! Rendering a [`PathEnv`] as shell script.
+//!
+//! # Examples
+//!
+//! ```
+//! use path_env::{shell::Shell, PathEnv};
+//!
+//! let path: PathEnv = vec!["/opt/it's here/bin"].into_iter().collect();
+//!
+//! assert_eq!(
+//!     Shell::Bash.export(&path).unwrap(),
+//!     r"export PATH='/opt/it'\''s here/bin'",
+//! );
+//! assert_eq!(
+//!     Shell::Fish.export(&path).unwrap(),
+//!     r"set -gx PATH '/opt/it\'s here/bin'",
+//! );
+//! ```
+//!
+//! To persist such script in a user's shell startup file, see [`rc`].
+//!
+//! [`PathEnv`]: ../struct.PathEnv.html
+//! [`rc`]:      rc/index.html
+
+pub mod rc;
+
+use crate::{separator, vars::ListVar, PathEnv};
+use std::{
+    borrow::Cow, error::Error, ffi::OsStr, fmt, path::Path, str::FromStr,
+};
+
+/// A shell to render script for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Shell {
+    /// The POSIX shell, such as `dash`.
+    Sh,
+    /// The Bourne Again shell.
+    Bash,
+    /// The Z shell.
+    Zsh,
+    /// The friendly interactive shell.
+    Fish,
+    /// PowerShell, including PowerShell Core.
+    PowerShell,
+    /// The Windows command prompt, `cmd.exe`.
+    Cmd,
+}
+
+impl Shell {
+    /// All supported shells.
+    pub const ALL: &'static [Shell] = &[
+        Shell::Sh,
+        Shell::Bash,
+        Shell::Zsh,
+        Shell::Fish,
+        Shell::PowerShell,
+        Shell::Cmd,
+    ];
+
+    /// Returns the usual name of the shell's executable.
+    #[inline]
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Sh => "sh",
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::PowerShell => "pwsh",
+            Shell::Cmd => "cmd",
+        }
+    }
+
+    /// Returns `word` as a single word in the shell's syntax.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShellError::Unrepresentable`] if `word` contains text that
+    /// the shell cannot express, such as a line break in `cmd.exe` or
+    /// non-UTF-8 bytes in PowerShell.
+    ///
+    /// [`ShellError::Unrepresentable`]: enum.ShellError.html#variant.Unrepresentable
+    pub fn quote<S: AsRef<OsStr>>(self, word: S) -> Result<String, ShellError> {
+        let pieces = pieces(word.as_ref());
+        let mut result = String::new();
+
+        if pieces.is_empty() {
+            return Ok(match self {
+                Shell::Cmd => String::new(),
+                _ => "''".to_owned(),
+            });
+        }
+
+        for (i, piece) in pieces.iter().enumerate() {
+            if i != 0 && self == Shell::PowerShell {
+                result.push_str(" + ");
+            }
+            match piece {
+                Piece::Text(text) => self.quote_text(text, &mut result)?,
+                Piece::Invalid(units) => {
+                    self.quote_invalid(units, &mut result)?
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn quote_text(
+        self,
+        text: &str,
+        out: &mut String,
+    ) -> Result<(), ShellError> {
+        match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => {
+                out.push('\'');
+                out.push_str(&text.replace('\'', r"'\''"));
+                out.push('\'');
+            }
+            Shell::Fish => {
+                out.push('\'');
+                out.push_str(&text.replace('\\', r"\\").replace('\'', r"\'"));
+                out.push('\'');
+            }
+            Shell::PowerShell => {
+                // PowerShell also treats typographic single quotes as quotes.
+                out.push('\'');
+                for c in text.chars() {
+                    if let '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}'
+                    | '\u{201B}' = c
+                    {
+                        out.push(c);
+                    }
+                    out.push(c);
+                }
+                out.push('\'');
+            }
+            Shell::Cmd => {
+                if text.contains(&['"', '\r', '\n'][..]) {
+                    return Err(ShellError::Unrepresentable(self));
+                }
+                out.push_str(&text.replace('%', "%%"));
+            }
+        }
+        Ok(())
+    }
+
+    fn quote_invalid(
+        self,
+        units: &[u16],
+        out: &mut String,
+    ) -> Result<(), ShellError> {
+        use fmt::Write;
+
+        // These are bytes on Unix-like systems, which only Unix shells can
+        // write, and unpaired surrogates on Windows, which only PowerShell can
+        // write.
+        if cfg!(windows) != (self == Shell::PowerShell) || self == Shell::Cmd {
+            return Err(ShellError::Unrepresentable(self));
+        }
+
+        match self {
+            Shell::Sh => {
+                out.push_str("\"$(printf '");
+                for unit in units {
+                    let _ = write!(out, "\\{:03o}", unit);
+                }
+                out.push_str("')\"");
+            }
+            Shell::Bash | Shell::Zsh => {
+                out.push_str("$'");
+                for unit in units {
+                    let _ = write!(out, "\\x{:02X}", unit);
+                }
+                out.push('\'');
+            }
+            Shell::Fish => {
+                for unit in units {
+                    let _ = write!(out, "\\x{:02X}", unit);
+                }
+            }
+            Shell::PowerShell => {
+                for (i, unit) in units.iter().enumerate() {
+                    if i != 0 {
+                        out.push_str(" + ");
+                    }
+                    let _ = write!(out, "[char]0x{:04X}", unit);
+                }
+            }
+            Shell::Cmd => {}
+        }
+        Ok(())
+    }
+
+    /// Returns a statement that sets and exports `PATH` to `path`.
+    ///
+    /// The result has no trailing line break.
+    #[inline]
+    pub fn export(self, path: &PathEnv) -> Result<String, ShellError> {
+        self.export_var(crate::vars::PATH, path)
+    }
+
+    /// Returns a statement that sets and exports the variable described by
+    /// `var` to `path`.
+    ///
+    /// The result has no trailing line break.
+    pub fn export_var<'a, V: Into<ListVar<'a>>>(
+        self,
+        var: V,
+        path: &PathEnv,
+    ) -> Result<String, ShellError> {
+        let var = var.into();
+        let name = checked_name(var.name())?;
+
+        let value = || self.quote(var.join(path));
+
+        Ok(match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => {
+                format!("export {}={}", name, value()?)
+            }
+            // Fish keeps path variables as lists and joins them on export.
+            Shell::Fish => {
+                let mut result = if name == "PATH" {
+                    String::from("set -gx PATH")
+                } else {
+                    format!("set -gx --path {}", name)
+                };
+                for part in path.iter() {
+                    result.push(' ');
+                    result.push_str(&self.quote(part)?);
+                }
+                result
+            }
+            Shell::PowerShell => format!("$env:{} = {}", name, value()?),
+            Shell::Cmd => format!("set \"{}={}\"", name, value()?),
+        })
+    }
+}
+
+/// Where to add an entry to `PATH`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum End {
+    Front,
+    Back,
+}
+
+impl Shell {
+    /// Returns a snippet that prepends `dir` to `PATH` when the script runs,
+    /// unless `PATH` already contains it.
+    ///
+    /// Running the snippet several times, such as by sourcing it from
+    /// multiple rc files, leaves `PATH` with a single copy of `dir`. An unset
+    /// or empty `PATH` does not gain an empty entry.
+    ///
+    /// The result has no trailing line break.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShellError::Unsupported`] for [`Shell::Cmd`], which has no
+    /// reliable way to search `PATH`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(unix)] {
+    /// use path_env::shell::Shell;
+    ///
+    /// let snippet = Shell::Sh.prepend_if_missing("/opt/tool/bin").unwrap();
+    /// assert_eq!(
+    ///     snippet,
+    ///     r#"case ":${PATH}:" in
+    ///     *:'/opt/tool/bin':*) ;;
+    ///     *) export PATH='/opt/tool/bin'"${PATH:+:${PATH}}" ;;
+    /// esac"#
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// [`ShellError::Unsupported`]: enum.ShellError.html#variant.Unsupported
+    /// [`Shell::Cmd`]:              enum.Shell.html#variant.Cmd
+    #[inline]
+    pub fn prepend_if_missing<P: AsRef<Path>>(
+        self,
+        dir: P,
+    ) -> Result<String, ShellError> {
+        self.add_if_missing(dir.as_ref(), End::Front)
+    }
+
+    /// Returns a snippet that appends `dir` to `PATH` when the script runs,
+    /// unless `PATH` already contains it.
+    ///
+    /// See [`prepend_if_missing`](#method.prepend_if_missing) for more info.
+    #[inline]
+    pub fn append_if_missing<P: AsRef<Path>>(
+        self,
+        dir: P,
+    ) -> Result<String, ShellError> {
+        self.add_if_missing(dir.as_ref(), End::Back)
+    }
+
+    fn add_if_missing(
+        self,
+        dir: &Path,
+        end: End,
+    ) -> Result<String, ShellError> {
+        let dir = self.quote(dir)?;
+        let sep = separator::STR;
+
+        Ok(match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => {
+                let value = match end {
+                    End::Front => {
+                        format!("{}\"${{PATH:+{}${{PATH}}}}\"", dir, sep)
+                    }
+                    End::Back => {
+                        format!("\"${{PATH:+${{PATH}}{}}}\"{}", sep, dir)
+                    }
+                };
+                format!(
+                    "case \"{sep}${{PATH}}{sep}\" in\n    \
+                     *{sep}{dir}{sep}*) ;;\n    \
+                     *) export PATH={value} ;;\n\
+                     esac",
+                    sep = sep,
+                    dir = dir,
+                    value = value,
+                )
+            }
+            Shell::Fish => {
+                let value = match end {
+                    End::Front => format!("{} $PATH", dir),
+                    End::Back => format!("$PATH {}", dir),
+                };
+                format!(
+                    "if not contains -- {dir} $PATH\n    \
+                     set -gx PATH {value}\n\
+                     end",
+                    dir = dir,
+                    value = value,
+                )
+            }
+            Shell::PowerShell => {
+                let value = match end {
+                    End::Front => format!(
+                        "({}) + $(if ($env:PATH) {{ '{}' + $env:PATH }})",
+                        dir, sep
+                    ),
+                    End::Back => format!(
+                        "$(if ($env:PATH) {{ $env:PATH + '{}' }}) + ({})",
+                        sep, dir
+                    ),
+                };
+                format!(
+                    "if (($env:PATH -split '{sep}') -notcontains ({dir})) {{\n    \
+                     $env:PATH = {value}\n\
+                     }}",
+                    sep = sep,
+                    dir = dir,
+                    value = value,
+                )
+            }
+            Shell::Cmd => return Err(ShellError::Unsupported(self)),
+        })
+    }
+}
+
+impl fmt::Display for Shell {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Shell {
+    type Err = ShellError;
+
+    /// Parses a shell from the name or path of its executable, such as `zsh`
+    /// or `/usr/bin/zsh`.
+    fn from_str(s: &str) -> Result<Self, ShellError> {
+        let name = s.rsplit(&['/', '\\'][..]).next().unwrap_or(s);
+        let name = name.strip_suffix(".exe").unwrap_or(name);
+
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "sh" | "dash" | "ash" | "ksh" => Shell::Sh,
+            "bash" => Shell::Bash,
+            "zsh" => Shell::Zsh,
+            "fish" => Shell::Fish,
+            "pwsh" | "powershell" => Shell::PowerShell,
+            "cmd" => Shell::Cmd,
+            _ => return Err(ShellError::UnknownShell(s.to_owned())),
+        })
+    }
+}
+
+/// The error returned when rendering shell script fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellError {
+    /// The text cannot be expressed in the shell's syntax.
+    Unrepresentable(Shell),
+    /// The variable name is not a valid identifier.
+    InvalidName(String),
+    /// The shell name is not recognized.
+    UnknownShell(String),
+    /// The shell cannot express the requested script.
+    Unsupported(Shell),
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShellError::Unrepresentable(shell) => {
+                write!(f, "text cannot be represented in {} syntax", shell)
+            }
+            ShellError::InvalidName(name) => {
+                write!(f, "invalid variable name: {:?}", name)
+            }
+            ShellError::UnknownShell(name) => {
+                write!(f, "unknown shell: {:?}", name)
+            }
+            ShellError::Unsupported(shell) => {
+                write!(f, "operation is not supported for {}", shell)
+            }
+        }
+    }
+}
+
+impl Error for ShellError {}
+
+/// Returns `name` if it is a valid variable name in every shell.
+fn checked_name(name: &str) -> Result<&str, ShellError> {
+    let mut bytes = name.bytes();
+    let valid = match bytes.next() {
+        Some(b) if b == b'_' || b.is_ascii_alphabetic() => {
+            bytes.all(|b| b == b'_' || b.is_ascii_alphanumeric())
+        }
+        _ => false,
+    };
+    if valid {
+        Ok(name)
+    } else {
+        Err(ShellError::InvalidName(name.to_owned()))
+    }
+}
+
+/// A run of text in an `OsStr`.
+enum Piece<'a> {
+    /// Valid Unicode.
+    Text(Cow<'a, str>),
+    /// Bytes that are not UTF-8 on Unix-like systems, or unpaired surrogates
+    /// on Windows.
+    Invalid(Vec<u16>),
+}
+
+#[cfg(unix)]
+fn pieces(s: &OsStr) -> Vec<Piece<'_>> {
+    use std::{os::unix::ffi::OsStrExt, str};
+
+    let mut bytes = s.as_bytes();
+    let mut pieces = Vec::new();
+
+    while !bytes.is_empty() {
+        let error = match str::from_utf8(bytes) {
+            Ok(text) => {
+                pieces.push(Piece::Text(text.into()));
+                break;
+            }
+            Err(error) => error,
+        };
+
+        let (valid, rest) = bytes.split_at(error.valid_up_to());
+        let invalid_len = error.error_len().unwrap_or(rest.len());
+
+        if !valid.is_empty() {
+            // SAFETY: `valid_up_to` is the length of the valid UTF-8 prefix.
+            let valid = unsafe { str::from_utf8_unchecked(valid) };
+            pieces.push(Piece::Text(valid.into()));
+        }
+
+        let units = rest[..invalid_len].iter().map(|&b| u16::from(b));
+        match pieces.last_mut() {
+            Some(Piece::Invalid(prev)) if valid.is_empty() => {
+                prev.extend(units)
+            }
+            _ => pieces.push(Piece::Invalid(units.collect())),
+        }
+
+        bytes = &rest[invalid_len..];
+    }
+
+    pieces
+}
+
+#[cfg(windows)]
+fn pieces(s: &OsStr) -> Vec<Piece<'_>> {
+    use std::os::windows::ffi::OsStrExt;
+
+    // Valid text is borrowed from the `str` form when possible.
+    if let Some(text) = s.to_str() {
+        return if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![Piece::Text(text.into())]
+        };
+    }
+
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+
+    for c in std::char::decode_utf16(s.encode_wide()) {
+        match c {
+            Ok(c) => text.push(c),
+            Err(error) => {
+                if !text.is_empty() {
+                    let text = std::mem::take(&mut text);
+                    pieces.push(Piece::Text(text.into()));
+                }
+                match pieces.last_mut() {
+                    Some(Piece::Invalid(prev)) => {
+                        prev.push(error.unpaired_surrogate())
+                    }
+                    _ => pieces
+                        .push(Piece::Invalid(vec![error.unpaired_surrogate()])),
+                }
+            }
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text.into()));
+    }
+
+    pieces
+}
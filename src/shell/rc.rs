@@ -0,0 +1,317 @@
+//! Managed blocks in shell startup files such as `.bashrc`.
+//!
+//! A managed block is a run of lines between two marker comments that a tool
+//! owns:
+//!
+//! ```text
+//! # >>> mytool path >>>
+//! ...
+//! # <<< mytool path <<<
+//! ```
+//!
+//! Installing a block replaces the previous version in place, or appends it
+//! if there is none. Uninstalling removes it. Files are only rewritten when
+//! their contents change, and are replaced atomically.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use path_env::shell::{rc::ManagedBlock, Shell};
+//! use std::path::Path;
+//!
+//! let home = Path::new("/home/me");
+//! let shell = Shell::Bash;
+//!
+//! let block = ManagedBlock::new("mytool path");
+//! let contents = shell.prepend_if_missing("/home/me/.mytool/bin")?;
+//!
+//! if let Some(rc_file) = shell.rc_file(home) {
+//!     block.install(rc_file, &contents)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{Shell, ShellError};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+impl Shell {
+    /// Returns the startup file under `home` that interactive sessions of the
+    /// shell read, or `None` if it has none.
+    ///
+    /// This is `.profile` for [`Sh`], `.bashrc` for [`Bash`], `.zshrc` for
+    /// [`Zsh`] and `.config/fish/config.fish` for [`Fish`].
+    ///
+    /// [`Sh`]:   enum.Shell.html#variant.Sh
+    /// [`Bash`]: enum.Shell.html#variant.Bash
+    /// [`Zsh`]:  enum.Shell.html#variant.Zsh
+    /// [`Fish`]: enum.Shell.html#variant.Fish
+    pub fn rc_file<P: AsRef<Path>>(self, home: P) -> Option<PathBuf> {
+        let name = match self {
+            Shell::Sh => ".profile",
+            Shell::Bash => ".bashrc",
+            Shell::Zsh => ".zshrc",
+            Shell::Fish => ".config/fish/config.fish",
+            Shell::PowerShell | Shell::Cmd => return None,
+        };
+        Some(home.as_ref().join(name))
+    }
+}
+
+/// Returns block contents that prepend each of `dirs` to `PATH` unless it is
+/// already there, keeping the order of `dirs`.
+///
+/// See [`Shell::prepend_if_missing`] for more info.
+///
+/// [`Shell::prepend_if_missing`]: ../enum.Shell.html#method.prepend_if_missing
+pub fn prepend_contents<P: AsRef<Path>>(
+    shell: Shell,
+    dirs: &[P],
+) -> Result<String, ShellError> {
+    let mut contents = String::new();
+
+    // Prepending in reverse leaves the first directory at the front.
+    for dir in dirs.iter().rev() {
+        contents.push_str(&shell.prepend_if_missing(dir)?);
+        contents.push('\n');
+    }
+
+    Ok(contents)
+}
+
+/// A block of lines in a shell startup file that is owned by one tool.
+///
+/// See the [module documentation](index.html) for more info.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ManagedBlock {
+    start: String,
+    end: String,
+}
+
+impl ManagedBlock {
+    /// Creates a block marked by `name`, such as `"mytool path"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains a line break.
+    pub fn new(name: &str) -> Self {
+        assert!(
+            !name.contains(&['\n', '\r'][..]),
+            "block name contains a line break: {:?}",
+            name
+        );
+        Self {
+            start: format!("# >>> {} >>>", name),
+            end: format!("# <<< {} <<<", name),
+        }
+    }
+
+    /// Returns the line that starts the block.
+    #[inline]
+    pub fn start_marker(&self) -> &str {
+        &self.start
+    }
+
+    /// Returns the line that ends the block.
+    #[inline]
+    pub fn end_marker(&self) -> &str {
+        &self.end
+    }
+
+    /// Returns the block with `contents` between its markers, ending in a line
+    /// break.
+    pub fn render(&self, contents: &str) -> String {
+        let contents = contents.trim_end_matches(&['\n', '\r'][..]);
+        let mut block = String::with_capacity(
+            self.start.len() + contents.len() + self.end.len() + 3,
+        );
+
+        block.push_str(&self.start);
+        block.push('\n');
+        if !contents.is_empty() {
+            block.push_str(contents);
+            block.push('\n');
+        }
+        block.push_str(&self.end);
+        block.push('\n');
+        block
+    }
+
+    /// Returns `text` with the block set to `contents`, or removed if
+    /// `contents` is `None`.
+    ///
+    /// The first existing block is replaced in place and any others are
+    /// removed. A new block is appended after a blank line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if a start marker is not
+    /// followed by an end marker, since the extent of the block is unknown.
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn update_str(
+        &self,
+        text: &str,
+        contents: Option<&str>,
+    ) -> io::Result<String> {
+        let mut lines = text.split_inclusive('\n').collect::<Vec<_>>();
+        let is_marker = |line: &str, marker: &str| line.trim_end() == marker;
+
+        // Find all blocks as `(start, end)` line ranges, inclusive.
+        let mut blocks = Vec::new();
+        let mut i = 0;
+        while i < lines.len() {
+            if is_marker(lines[i], &self.start) {
+                let end = lines[i + 1..]
+                    .iter()
+                    .position(|line| is_marker(line, &self.end))
+                    .map(|end| i + 1 + end)
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "missing {:?} after line {}",
+                                self.end,
+                                i + 1
+                            ),
+                        )
+                    })?;
+                blocks.push((i, end));
+                i = end;
+            }
+            i += 1;
+        }
+
+        let rendered = contents.map(|contents| self.render(contents));
+
+        let first = match blocks.first() {
+            Some(&(start, _)) => start,
+            None => {
+                let mut result = text.to_owned();
+                if let Some(rendered) = rendered {
+                    if !result.is_empty() {
+                        if !result.ends_with('\n') {
+                            result.push('\n');
+                        }
+                        result.push('\n');
+                    }
+                    result.push_str(&rendered);
+                }
+                return Ok(result);
+            }
+        };
+
+        for &(start, end) in blocks.iter().rev() {
+            lines.drain(start..=end);
+        }
+
+        match &rendered {
+            Some(rendered) => lines.insert(first, rendered.as_str()),
+            // Also remove the blank line that was added before the block.
+            None if first == lines.len()
+                && first > 0
+                && lines[first - 1].trim().is_empty() =>
+            {
+                lines.pop();
+            }
+            None => {}
+        }
+
+        Ok(lines.concat())
+    }
+
+    /// Sets the block in `file` to `contents`, creating the file if needed.
+    ///
+    /// Returns `true` if the file changed. If `file` is a symbolic link, the
+    /// file it points to is updated.
+    #[inline]
+    pub fn install<P: AsRef<Path>>(
+        &self,
+        file: P,
+        contents: &str,
+    ) -> io::Result<bool> {
+        self.update_file(file.as_ref(), Some(contents))
+    }
+
+    /// Removes the block from `file`.
+    ///
+    /// Returns `true` if the file changed. A missing file is left as-is.
+    #[inline]
+    pub fn uninstall<P: AsRef<Path>>(&self, file: P) -> io::Result<bool> {
+        self.update_file(file.as_ref(), None)
+    }
+
+    fn update_file(
+        &self,
+        file: &Path,
+        contents: Option<&str>,
+    ) -> io::Result<bool> {
+        // Write through symbolic links so that managed dotfiles stay links.
+        let file = match fs::canonicalize(file) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                if contents.is_none() {
+                    return Ok(false);
+                }
+                file.to_owned()
+            }
+            Err(error) => return Err(error),
+        };
+
+        let old = match fs::read_to_string(&file) {
+            Ok(old) => Some(old),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error),
+        };
+
+        let new = self.update_str(old.as_deref().unwrap_or(""), contents)?;
+        if old.as_deref() == Some(&new) {
+            return Ok(false);
+        }
+
+        write_atomic(&file, &new)?;
+        Ok(true)
+    }
+}
+
+/// Replaces `file` with `contents` by renaming a temporary file over it.
+fn write_atomic(file: &Path, contents: &str) -> io::Result<()> {
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    // The counter keeps threads of the same process from sharing a name.
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    let mut temp_name = file.file_name().unwrap_or_default().to_owned();
+    temp_name.push(format!(".{}.{}.tmp", process::id(), count));
+    let temp = dir.join(temp_name);
+
+    let result = (|| {
+        // Syncing requires write access on Windows, so it goes through the
+        // handle that wrote the contents.
+        let mut temp_file = fs::File::create(&temp)?;
+        temp_file.write_all(contents.as_bytes())?;
+        if let Ok(meta) = fs::metadata(file) {
+            temp_file.set_permissions(meta.permissions())?;
+        }
+        temp_file.sync_all()?;
+        drop(temp_file);
+        fs::rename(&temp, file)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
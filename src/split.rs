@@ -79,14 +79,36 @@ pub fn split<P: ?Sized + AsRef<OsStr>>(unparsed: &P) -> PathEnvSplit {
 /// assert_eq!(paths.len(), 2);
 /// ```
 #[derive(Clone, Debug)]
-pub struct PathEnvSplit<'a>(&'a OsStr);
+pub struct PathEnvSplit<'a> {
+    path: &'a OsStr,
+    separator: u8,
+}
 
 impl<'a> PathEnvSplit<'a> {
     /// Creates an instance from a reference to the contents of a `PATH`
     /// environment variable.
     #[inline]
     pub fn new<P: ?Sized + AsRef<OsStr>>(unparsed: &'a P) -> Self {
-        Self(unparsed.as_ref())
+        Self::with_separator(unparsed, crate::separator::U8)
+    }
+
+    /// Creates an instance from a reference to the contents of a list variable
+    /// that uses `separator` instead of the `PATH` separator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `separator` is not an ASCII byte, since entries could then
+    /// be split in the middle of a character.
+    #[inline]
+    pub fn with_separator<P: ?Sized + AsRef<OsStr>>(
+        unparsed: &'a P,
+        separator: u8,
+    ) -> Self {
+        assert!(separator.is_ascii(), "separator must be ASCII");
+        Self {
+            path: unparsed.as_ref(),
+            separator,
+        }
     }
 }
 
@@ -95,21 +117,21 @@ impl<'a> Iterator for PathEnvSplit<'a> {
 
     // TODO: Handle quote pairs on Windows.
     fn next(&mut self) -> Option<&'a Path> {
-        fn next_separator(bytes: &[u8]) -> Option<usize> {
+        fn next_separator(bytes: &[u8], sep: u8) -> Option<usize> {
             #[cfg(feature = "memchr")]
             {
-                memchr::memchr(crate::separator::U8, bytes)
+                memchr::memchr(sep, bytes)
             }
 
             #[cfg(not(feature = "memchr"))]
             {
-                bytes.iter().position(|&b| b == crate::separator::U8)
+                bytes.iter().position(|&b| b == sep)
             }
         }
 
-        let mut path = self.0.as_bytes();
+        let mut path = self.path.as_bytes();
 
-        while let Some(i) = next_separator(path) {
+        while let Some(i) = next_separator(path, self.separator) {
             let next = &path[..i];
             path = &path[(i + 1)..];
 
@@ -117,13 +139,13 @@ impl<'a> Iterator for PathEnvSplit<'a> {
                 continue;
             } else {
                 unsafe {
-                    self.0 = OsStr::from_bytes(path);
+                    self.path = OsStr::from_bytes(path);
                     return Some(Path::from_bytes(next));
                 }
             }
         }
 
-        self.0 = Default::default();
+        self.path = Default::default();
 
         if path.is_empty() {
             None
@@ -134,19 +156,19 @@ impl<'a> Iterator for PathEnvSplit<'a> {
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        fn count(bytes: &[u8]) -> usize {
+        fn count(bytes: &[u8], sep: u8) -> usize {
             #[cfg(feature = "bytecount")]
             {
-                bytecount::count(bytes, crate::separator::U8)
+                bytecount::count(bytes, sep)
             }
 
             #[cfg(not(feature = "bytecount"))]
             #[allow(clippy::naive_bytecount)]
             {
-                bytes.iter().filter(|&&b| b == crate::separator::U8).count()
+                bytes.iter().filter(|&&b| b == sep).count()
             }
         }
-        (0, Some(count(self.0.as_bytes()) + 1))
+        (0, Some(count(self.path.as_bytes(), self.separator) + 1))
     }
 
     #[inline]
@@ -158,13 +180,13 @@ impl<'a> Iterator for PathEnvSplit<'a> {
 impl<'a> DoubleEndedIterator for PathEnvSplit<'a> {
     // TODO: Handle quote pairs on Windows.
     fn next_back(&mut self) -> Option<&'a Path> {
-        fn next_separator(bytes: &[u8]) -> Option<usize> {
-            bytes.iter().rev().position(|&b| b == crate::separator::U8)
+        fn next_separator(bytes: &[u8], sep: u8) -> Option<usize> {
+            bytes.iter().rposition(|&b| b == sep)
         }
 
-        let mut path = self.0.as_bytes();
+        let mut path = self.path.as_bytes();
 
-        while let Some(i) = next_separator(path) {
+        while let Some(i) = next_separator(path, self.separator) {
             let next = &path[(i + 1)..];
             path = &path[..i];
 
@@ -172,13 +194,13 @@ impl<'a> DoubleEndedIterator for PathEnvSplit<'a> {
                 continue;
             } else {
                 unsafe {
-                    self.0 = OsStr::from_bytes(path);
+                    self.path = OsStr::from_bytes(path);
                     return Some(Path::from_bytes(next));
                 }
             }
         }
 
-        self.0 = Default::default();
+        self.path = Default::default();
 
         if path.is_empty() {
             None
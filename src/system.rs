@@ -0,0 +1,483 @@
+//! System configuration that determines the `PATH` of new sessions.
+//!
+//! [`PathEnv::from_var`] only reports the `PATH` of the current process, which
+//! may have been changed by a shell, a service manager or the program itself.
+//! The functions in this module instead read the files that decide which
+//! `PATH` login sessions and services start with.
+//!
+//! Missing files and directories are treated as empty, since most systems
+//! only have some of them.
+//!
+//! [`PathEnv::from_var`]: ../struct.PathEnv.html#method.from_var
+
+use crate::{lock, PathEnv};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The file read by `pam_env` when a user logs in.
+pub const ETC_ENVIRONMENT: &str = "/etc/environment";
+
+/// The system directories searched for `environment.d` files, from highest to
+/// lowest priority.
+///
+/// See [`environment_d`] for more info.
+///
+/// [`environment_d`]: fn.environment_d.html
+pub const ENVIRONMENT_D_DIRS: &[&str] = &[
+    "/etc/environment.d",
+    "/run/environment.d",
+    "/usr/local/lib/environment.d",
+    "/usr/lib/environment.d",
+];
+
+/// The base list read by `path_helper` on macOS.
+pub const ETC_PATHS: &str = "/etc/paths";
+
+/// The drop-in directory read by `path_helper` on macOS after
+/// [`ETC_PATHS`](constant.ETC_PATHS.html).
+pub const ETC_PATHS_D: &str = "/etc/paths.d";
+
+/// The file from which `login` and `su` read their defaults.
+pub const LOGIN_DEFS: &str = "/etc/login.defs";
+
+/// Returns `PATH` as set by [`/etc/environment`], or `None` if it is not set
+/// there.
+///
+/// [`/etc/environment`]: constant.ETC_ENVIRONMENT.html
+#[inline]
+pub fn etc_environment() -> io::Result<Option<PathEnv>> {
+    environment_file(ETC_ENVIRONMENT)
+}
+
+/// Returns `PATH` as set by `file`, which is in the format of
+/// `/etc/environment`.
+///
+/// See [`parse_environment`] for the format.
+///
+/// [`parse_environment`]: fn.parse_environment.html
+pub fn environment_file<P: AsRef<Path>>(
+    file: P,
+) -> io::Result<Option<PathEnv>> {
+    match read_optional(file.as_ref())? {
+        Some(text) => Ok(parse_environment(&text)),
+        None => Ok(None),
+    }
+}
+
+/// Returns `PATH` as set by `text`, which is in the format of
+/// `/etc/environment`.
+///
+/// Each line is a `KEY=value` assignment, optionally preceded by `export`.
+/// The value may be wrapped in single or double quotes, which are removed.
+/// No variables are expanded. Blank lines, `#` comments and malformed lines
+/// are ignored, and the last assignment wins.
+///
+/// # Examples
+///
+/// ```
+/// use path_env::system;
+///
+/// let text = "LANG=C.UTF-8\nPATH=\"/usr/local/bin:/usr/bin\"\n";
+/// let path = system::parse_environment(text).unwrap();
+///
+/// assert_eq!(path.as_os_str(), "/usr/local/bin:/usr/bin");
+/// ```
+pub fn parse_environment(text: &str) -> Option<PathEnv> {
+    // The last assignment wins.
+    text.lines()
+        .rev()
+        .filter_map(parse_line)
+        .find(|(key, _)| *key == "PATH")
+        .map(|(_, value)| PathEnv::from(unquote(value).to_owned()))
+}
+
+/// Returns `PATH` after applying the `*.conf` files in `dirs`, as done by
+/// `systemd` for user services and some login sessions.
+///
+/// `dirs` are ordered from highest to lowest priority. A file in one directory
+/// hides files of the same name in later directories, and the remaining files
+/// are applied in lexical order of their names. `path` is the value of `PATH`
+/// before any file is applied.
+///
+/// Each line is a `KEY=value` assignment. Values may refer to variables
+/// assigned so far, including `PATH`, as `$KEY` or `${KEY}`. The forms
+/// `${KEY:-default}` and `${KEY:+alternate}` are supported as well. Values in
+/// single quotes are not expanded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use path_env::{system, PathEnv};
+///
+/// let base = PathEnv::from(String::from("/usr/local/bin:/usr/bin:/bin"));
+/// let path = system::environment_d(system::ENVIRONMENT_D_DIRS, Some(base))?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn environment_d<P: AsRef<Path>>(
+    dirs: &[P],
+    path: Option<PathEnv>,
+) -> io::Result<Option<PathEnv>> {
+    // Keyed by file name so that earlier directories win and iteration is in
+    // lexical order.
+    let mut files = BTreeMap::<OsString, PathBuf>::new();
+
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            if Path::new(&name).extension() == Some(OsStr::new("conf")) {
+                files.entry(name).or_insert_with(|| entry.path());
+            }
+        }
+    }
+
+    let mut vars = HashMap::new();
+    if let Some(path) = path {
+        let path = path.into_os_string().into_string().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "PATH is not UTF-8")
+        })?;
+        vars.insert("PATH".to_owned(), path);
+    }
+
+    for file in files.values() {
+        let text = read_optional(file)?.unwrap_or_default();
+        for (key, value) in text.lines().filter_map(parse_line) {
+            let value = if is_single_quoted(value) {
+                unquote(value).to_owned()
+            } else {
+                expand(unquote(value), &vars)
+            };
+            vars.insert(key.to_owned(), value);
+        }
+    }
+
+    Ok(vars.remove("PATH").map(PathEnv::from))
+}
+
+/// Returns `PATH` after applying the `environment.d` files of the current
+/// user on top of `path`.
+///
+/// This searches `$XDG_CONFIG_HOME/environment.d`, defaulting to
+/// `$HOME/.config/environment.d`, followed by [`ENVIRONMENT_D_DIRS`].
+///
+/// See [`environment_d`] for more info.
+///
+/// [`ENVIRONMENT_D_DIRS`]: constant.ENVIRONMENT_D_DIRS.html
+/// [`environment_d`]:      fn.environment_d.html
+pub fn user_environment_d(
+    path: Option<PathEnv>,
+) -> io::Result<Option<PathEnv>> {
+    let config_home = lock::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| Path::new(dir).is_absolute())
+        .map(PathBuf::from)
+        .or_else(|| {
+            lock::var_os("HOME").map(|home| Path::new(&home).join(".config"))
+        });
+
+    let mut dirs = Vec::with_capacity(ENVIRONMENT_D_DIRS.len() + 1);
+    dirs.extend(config_home.map(|dir| dir.join("environment.d")));
+    dirs.extend(ENVIRONMENT_D_DIRS.iter().map(PathBuf::from));
+
+    environment_d(&dirs, path)
+}
+
+/// Returns the `PATH` that `path_helper` builds from [`/etc/paths`] and
+/// [`/etc/paths.d`].
+///
+/// See [`paths`] for more info.
+///
+/// [`/etc/paths`]:   constant.ETC_PATHS.html
+/// [`/etc/paths.d`]: constant.ETC_PATHS_D.html
+/// [`paths`]:        fn.paths.html
+#[inline]
+pub fn etc_paths() -> io::Result<PathEnv> {
+    paths(ETC_PATHS, ETC_PATHS_D)
+}
+
+/// Returns the entries listed in `file`, followed by those listed in the files
+/// of `dir` in sorted order, as done by `path_helper`.
+///
+/// Files whose names start with `.` are skipped. See [`parse_paths`] for the
+/// format of each file.
+///
+/// [`parse_paths`]: fn.parse_paths.html
+pub fn paths<F, D>(file: F, dir: D) -> io::Result<PathEnv>
+where
+    F: AsRef<Path>,
+    D: AsRef<Path>,
+{
+    let mut path = PathEnv::empty();
+    if let Some(text) = read_optional(file.as_ref())? {
+        extend_paths(&mut path, &text);
+    }
+
+    let mut files = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(error) => return Err(error),
+    };
+    files.retain(|file| match file.file_name() {
+        Some(name) => !name.to_string_lossy().starts_with('.'),
+        None => false,
+    });
+    files.sort();
+
+    for file in files {
+        if file.is_dir() {
+            continue;
+        }
+        if let Some(text) = read_optional(&file)? {
+            extend_paths(&mut path, &text);
+        }
+    }
+
+    Ok(path)
+}
+
+/// Returns the entries listed in `text`, which is in the format of
+/// `/etc/paths`.
+///
+/// Each non-blank line is an entry, with surrounding whitespace removed.
+/// Entries that were already listed are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use path_env::system;
+///
+/// let path = system::parse_paths("/usr/local/bin\n/usr/bin\n\n/usr/bin\n");
+///
+/// assert_eq!(path.as_os_str(), "/usr/local/bin:/usr/bin");
+/// ```
+pub fn parse_paths(text: &str) -> PathEnv {
+    let mut path = PathEnv::empty();
+    extend_paths(&mut path, text);
+    path
+}
+
+fn extend_paths(path: &mut PathEnv, text: &str) {
+    for line in text.lines().map(str::trim) {
+        if !line.is_empty() && !path.contains(line) {
+            path.push_back(line);
+        }
+    }
+}
+
+/// The `PATH` defaults of a `login.defs` file.
+///
+/// # Examples
+///
+/// ```
+/// use path_env::system::LoginDefs;
+///
+/// let defs = LoginDefs::parse(
+///     "# Default PATH settings\n\
+///      ENV_SUPATH PATH=/usr/sbin:/usr/bin:/sbin:/bin\n\
+///      ENV_PATH   PATH=/usr/local/bin:/usr/bin:/bin\n",
+/// );
+///
+/// let path = defs.path().unwrap();
+/// assert_eq!(path.as_os_str(), "/usr/local/bin:/usr/bin:/bin");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoginDefs {
+    path: Option<PathEnv>,
+    supath: Option<PathEnv>,
+}
+
+impl LoginDefs {
+    /// Reads [`/etc/login.defs`](constant.LOGIN_DEFS.html).
+    #[inline]
+    pub fn from_system() -> io::Result<Self> {
+        Self::read(LOGIN_DEFS)
+    }
+
+    /// Reads `file`, which is in the format of `/etc/login.defs`.
+    ///
+    /// A missing file has no settings.
+    pub fn read<P: AsRef<Path>>(file: P) -> io::Result<Self> {
+        Ok(read_optional(file.as_ref())?
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default())
+    }
+
+    /// Parses `text`, which is in the format of `/etc/login.defs`.
+    ///
+    /// Each line is a key followed by whitespace and a value, which may be
+    /// wrapped in double quotes. Blank lines and `#` comments are ignored, and
+    /// the last setting wins. A leading `PATH=` in the value is removed.
+    pub fn parse(text: &str) -> Self {
+        let mut defs = Self::default();
+
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.splitn(2, char::is_whitespace);
+            let key = fields.next().unwrap_or_default();
+            let value = fields.next().unwrap_or_default().trim();
+
+            let slot = match key {
+                "ENV_PATH" => &mut defs.path,
+                "ENV_SUPATH" => &mut defs.supath,
+                _ => continue,
+            };
+
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            let value = value.strip_prefix("PATH=").unwrap_or(value);
+
+            *slot = Some(PathEnv::from(value.to_owned()));
+        }
+
+        defs
+    }
+
+    /// Returns the `PATH` set by `login` for normal users, from `ENV_PATH`.
+    #[inline]
+    pub fn path(&self) -> Option<&PathEnv> {
+        self.path.as_ref()
+    }
+
+    /// Returns the `PATH` set by `login` and `su` for root, from
+    /// `ENV_SUPATH`.
+    #[inline]
+    pub fn supath(&self) -> Option<&PathEnv> {
+        self.supath.as_ref()
+    }
+}
+
+/// Reads `file`, returning `None` if it does not exist.
+fn read_optional(file: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(file) {
+        Ok(text) => Ok(Some(text)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Splits a `KEY=value` line, returning `None` for blank lines, comments and
+/// malformed lines. The value is trimmed but still quoted.
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return None;
+    }
+
+    let line = match line.strip_prefix("export") {
+        Some(rest) if rest.starts_with(char::is_whitespace) => {
+            rest.trim_start()
+        }
+        _ => line,
+    };
+
+    let eq = line.find('=')?;
+    let key = line[..eq].trim_end();
+    let value = line[eq + 1..].trim_start();
+
+    let mut chars = key.chars();
+    let is_valid = match chars.next() {
+        Some(c) => c == '_' || c.is_ascii_alphabetic(),
+        None => false,
+    } && chars.all(|c| c == '_' || c.is_ascii_alphanumeric());
+
+    if is_valid {
+        Some((key, value))
+    } else {
+        None
+    }
+}
+
+fn is_single_quoted(value: &str) -> bool {
+    value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'')
+}
+
+/// Removes a matching pair of quotes around `value`.
+fn unquote(value: &str) -> &str {
+    let bytes = value.as_bytes();
+    match bytes {
+        [first @ b'"', .., last] | [first @ b'\'', .., last]
+            if first == last =>
+        {
+            &value[1..value.len() - 1]
+        }
+        _ => value,
+    }
+}
+
+/// Expands `$KEY`, `${KEY}`, `${KEY:-default}` and `${KEY:+alternate}` in
+/// `value`. Unset variables expand to nothing.
+fn expand(value: &str, vars: &HashMap<String, String>) -> String {
+    let lookup = |name: &str| vars.get(name).map_or("", String::as_str);
+
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(dollar) = rest.find('$') {
+        result.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+
+        if let Some(braced) = rest.strip_prefix('{') {
+            let end = match braced.find('}') {
+                Some(end) => end,
+                None => {
+                    result.push('$');
+                    continue;
+                }
+            };
+            let inner = &braced[..end];
+            rest = &braced[end + 1..];
+
+            match inner.find(':') {
+                None => result.push_str(lookup(inner)),
+                Some(colon) => {
+                    let var = lookup(&inner[..colon]);
+                    let modifier = &inner[colon + 1..];
+                    let (op, word) = match modifier.get(..1) {
+                        Some(op) => (op, &modifier[1..]),
+                        None => ("", modifier),
+                    };
+                    match op {
+                        "-" if var.is_empty() => result.push_str(word),
+                        "-" => result.push_str(var),
+                        "+" if !var.is_empty() => result.push_str(word),
+                        "+" => {}
+                        // Unknown operators are kept as written.
+                        _ => {
+                            result.push_str("${");
+                            result.push_str(inner);
+                            result.push('}');
+                        }
+                    }
+                }
+            }
+        } else {
+            let len = rest
+                .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            if len == 0 || rest.as_bytes()[0].is_ascii_digit() {
+                result.push('$');
+            } else {
+                result.push_str(lookup(&rest[..len]));
+                rest = &rest[len..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
@@ -1,5 +1,5 @@
 use super::*;
-use std::{env, fmt::Write, path::PathBuf};
+use std::{env, fmt::Write, fs, path::PathBuf, process};
 
 const SEP: &str = separator::STR;
 
@@ -14,6 +14,24 @@ fn path_env_outputs(s: &str) -> Vec<PathBuf> {
     split(s).map(|path| path.to_owned()).collect()
 }
 
+/// Returns a fresh directory for the test called `name`.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        env::temp_dir().join(format!("path_env-{}-{}", process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Creates an executable file at `path`.
+#[cfg(unix)]
+fn write_executable(path: &std::path::Path, contents: &str) {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::write(path, contents).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
 #[test]
 fn std_compatible() {
     let a = "/path/to/bin";
@@ -125,3 +143,953 @@ fn std_compatible() {
         );
     }
 }
+
+#[test]
+fn contract_round_trip() {
+    let home = env::temp_dir().join("home");
+    let cargo = home.join(".cargo");
+
+    let path: PathEnv = vec![
+        cargo.join("bin"),
+        home.join("bin"),
+        home.clone(),
+        PathBuf::from("relative"),
+    ]
+    .into_iter()
+    .collect();
+
+    let mut rules = Contraction::new();
+    rules.home(&home).var("CARGO_HOME", &cargo);
+
+    let contracted = path.contract(&rules);
+    assert_ne!(contracted, path.as_os_str());
+
+    let expanded = PathEnv::expand_with(&contracted, |name| {
+        if name == "CARGO_HOME" {
+            Some(cargo.clone().into())
+        } else if name == "HOME" || name == "USERPROFILE" {
+            Some(home.clone().into())
+        } else {
+            None
+        }
+    });
+    assert_eq!(expanded, path);
+
+    // Names that cannot be referenced are not used.
+    #[cfg(unix)]
+    {
+        let mut rules = Contraction::new();
+        rules
+            .var("MY-TOOLS", "/opt/my-tools")
+            .var("1X", "/opt/x")
+            .var("_X1", "/opt/y");
+
+        let path =
+            PathEnv::from("/opt/my-tools/bin:/opt/x/x:/opt/y".to_owned());
+        let contracted = path.contract(&rules);
+        assert_eq!(contracted, "/opt/my-tools/bin:/opt/x/x:$_X1");
+
+        let expanded = PathEnv::expand_with(&contracted, |name| {
+            if name == "_X1" {
+                Some("/opt/y".into())
+            } else {
+                None
+            }
+        });
+        assert_eq!(expanded.as_os_str(), path.as_os_str());
+    }
+
+    // Literal references cannot be escaped, so they do not survive.
+    #[cfg(unix)]
+    {
+        let path = PathEnv::from("/opt/$HOME/bin".to_owned());
+        let contracted = path.contract(&rules);
+        assert_eq!(contracted, "/opt/$HOME/bin");
+
+        let expanded = PathEnv::expand_with(&contracted, |name| {
+            if name == "HOME" {
+                Some("/root".into())
+            } else {
+                None
+            }
+        });
+        assert_eq!(expanded.as_os_str(), "/opt//root/bin");
+    }
+}
+
+#[test]
+fn split_rev() {
+    let rev = |s: &str| {
+        split(s)
+            .rev()
+            .map(|entry| entry.to_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(rev(&format!("a{}bc", SEP)), ["bc", "a"]);
+    assert_eq!(rev(&format!("{0}{0}abc{0}{0}d{0}", SEP)), ["d", "abc"]);
+    assert_eq!(split(&format!("ab{}c", SEP)).last(), Some("c".as_ref()));
+}
+
+#[test]
+fn list_var_empty_entries() {
+    let value = format!("{0}a{0}{0}b{0}", SEP);
+    let value = OsStr::new(&value);
+
+    let ignored = vars::ListVar::new("X").parse(value);
+    assert_eq!(ignored, *format!("a{}b", SEP));
+
+    let current = vars::ListVar::new("X")
+        .with_empty(vars::EmptyEntry::CurrentDir)
+        .parse(value);
+    assert_eq!(current, *format!(".{0}a{0}.{0}b{0}.", SEP));
+
+    let comma = vars::ListVar::new("X").with_separator(b',');
+    let path = comma.parse(OsStr::new("a,,b"));
+    assert_eq!(path, *format!("a{}b", SEP));
+    assert_eq!(comma.join(&path), "a,b");
+
+    assert_eq!(vars::ListVar::from("MANPATH"), vars::MANPATH);
+}
+
+#[test]
+fn non_ascii_separator_panics() {
+    use std::panic::catch_unwind;
+
+    assert!(
+        catch_unwind(|| vars::ListVar::new("X").with_separator(0xC3)).is_err()
+    );
+    assert!(catch_unwind(|| PathEnvSplit::with_separator("a", 0x80)).is_err());
+}
+
+#[test]
+fn list_var_resolve_default() {
+    let default: PathEnv = vec!["d1", "d2"].into_iter().collect();
+    let resolve =
+        |value: &str| vars::MANPATH.resolve(Some(OsStr::new(value)), &default);
+
+    assert_eq!(resolve(""), default);
+    assert_eq!(resolve(SEP), *format!("d1{0}d2{0}d1{0}d2", SEP));
+    assert_eq!(resolve(&format!("a{}", SEP)), *format!("a{0}d1{0}d2", SEP));
+    assert_eq!(
+        resolve(&format!("a{0}{0}b", SEP)),
+        *format!("a{0}d1{0}d2{0}b", SEP)
+    );
+
+    let xdg = vars::XDG_CONFIG_DIRS.resolve(None, &default);
+    assert_eq!(xdg, default);
+}
+
+#[test]
+#[cfg(unix)]
+fn which_uses_path_env() {
+    use std::process::Command;
+
+    let dir = temp_dir("which");
+    let (a, b) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(&a).unwrap();
+    fs::create_dir_all(&b).unwrap();
+
+    write_executable(&a.join("tool"), "#!/bin/sh\nexit 3\n");
+    write_executable(&b.join("tool"), "#!/bin/sh\nexit 4\n");
+    fs::write(a.join("data"), "").unwrap();
+
+    let path: PathEnv = vec![&a, &b].into_iter().collect();
+
+    assert_eq!(path.which("tool"), Some(a.join("tool")));
+    assert_eq!(path.which_all("tool").count(), 2);
+    assert_eq!(path.which("data"), None);
+
+    let status = Command::with_path_env("tool", &path)
+        .unwrap()
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(3));
+
+    let error = Command::with_path_env("missing", &path).unwrap_err();
+    assert_eq!(error.program(), "missing");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn for_command_overrides() {
+    use std::process::Command;
+
+    // Keep other tests from changing `PATH` in between.
+    let _lock = lock::ENV.lock();
+
+    let mut command = Command::new("program");
+    assert_eq!(PathEnv::for_command(&command), PathEnv::from_var());
+
+    let path = format!("a{}b", SEP);
+    command.env("PATH", &path);
+    assert_eq!(PathEnv::for_command(&command).unwrap(), *path);
+
+    command.env_remove("PATH");
+    assert_eq!(PathEnv::for_command(&command), None);
+
+    // A cleared environment is only known when the caller says so.
+    let mut command = Command::new("program");
+    command.env_clear();
+    assert_eq!(PathEnv::for_command(&command), PathEnv::from_var());
+    assert_eq!(PathEnv::for_command_with(&command, true), None);
+    assert_eq!(
+        PathEnv::for_command_with(&command, false),
+        PathEnv::from_var()
+    );
+
+    command.env("PATH", &path);
+    assert_eq!(PathEnv::for_command_with(&command, true).unwrap(), *path);
+}
+
+#[test]
+fn set_var_scoped_restores() {
+    let _lock = lock::ENV.lock();
+
+    let original = env::var_os("PATH");
+    let outer: PathEnv = vec!["outer"].into_iter().collect();
+    let inner: PathEnv = vec!["inner"].into_iter().collect();
+
+    {
+        let _outer = outer.set_var_scoped();
+        {
+            let inner = inner.set_var_scoped();
+            assert_eq!(
+                inner.previous(),
+                Some(outer.as_os_str().to_owned()).as_ref()
+            );
+            assert_eq!(var().unwrap(), *"inner");
+        }
+        assert_eq!(var(), Some(outer.clone()));
+
+        env::remove_var("PATH");
+        let unset = inner.set_var_scoped();
+        assert_eq!(unset.previous(), None);
+        drop(unset);
+        assert_eq!(env::var_os("PATH"), None);
+    }
+
+    assert_eq!(env::var_os("PATH"), original);
+}
+
+#[test]
+fn process_path_update_is_atomic() {
+    use std::thread;
+
+    let original = lock::var_os("PATH");
+
+    let threads = (0..8)
+        .map(|i| {
+            thread::spawn(move || {
+                ProcessPath::update(|path| path.push_back(format!("t{}", i)))
+            })
+        })
+        .collect::<Vec<_>>();
+    threads.into_iter().for_each(|t| t.join().unwrap());
+
+    let process = ProcessPath::lock();
+    let path = process.get().unwrap();
+    assert!((0..8).all(|i| path.contains(format!("t{}", i))));
+
+    match original {
+        Some(original) => env::set_var("PATH", original),
+        None => process.remove(),
+    }
+}
+
+#[test]
+fn diff_reports_moves() {
+    let old: PathEnv = vec!["a", "b", "c", "d"].into_iter().collect();
+    let new: PathEnv = vec!["c", "a", "b", "e"].into_iter().collect();
+
+    let diff = old.diff(&new);
+    let changes = diff.iter().copied().collect::<Vec<_>>();
+
+    assert_eq!(
+        changes,
+        [
+            PathChange::Removed {
+                index: 3,
+                path: "d".as_ref()
+            },
+            PathChange::Moved {
+                from: 2,
+                to: 0,
+                path: "c".as_ref()
+            },
+            PathChange::Added {
+                index: 3,
+                path: "e".as_ref()
+            },
+        ]
+    );
+    assert!(old.diff(&old.clone()).is_empty());
+}
+
+#[test]
+fn patch_inverse_is_exact() {
+    let original: PathEnv = vec!["a", "x", "b", "x", "c"].into_iter().collect();
+
+    let mut patch = PathPatch::new();
+    patch
+        .prepend("x")
+        .append("a")
+        .remove("b")
+        .insert_before("y", "c")
+        .insert_after("z", "missing")
+        .move_to_front("c");
+
+    let mut path = original.clone();
+    let undo = patch.apply(&mut path);
+    assert_eq!(path, *format!("c{0}x{0}y{0}a", SEP));
+
+    let again = path.clone();
+    patch.apply(&mut path);
+    assert_eq!(path, again);
+
+    undo.apply(&mut path);
+    assert_eq!(path.as_os_str(), original.as_os_str());
+
+    // Entries are restored as written, not as in the patch.
+    let original = PathEnv::from(format!("/usr/bin/{}/bin", SEP));
+    let mut path = original.clone();
+    let undo = PathPatch::new().prepend("/usr/bin").apply(&mut path);
+    assert_eq!(path.as_os_str(), &*format!("/usr/bin{}/bin", SEP));
+
+    undo.apply(&mut path);
+    assert_eq!(path.as_os_str(), original.as_os_str());
+}
+
+/// Returns where `shell` is installed, or `None` if it is optional and
+/// missing.
+#[cfg(unix)]
+fn shell_program(shell: shell::Shell) -> Option<PathBuf> {
+    // `from_var` holds the lock, so tests that change `PATH` are not midway.
+    let program = PathEnv::from_var().and_then(|path| path.which(shell.name()));
+    match shell {
+        shell::Shell::Sh => {
+            Some(program.expect("`sh` is required to run these tests"))
+        }
+        _ => program,
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn shell_export_round_trip() {
+    use shell::Shell;
+    use std::{os::unix::ffi::OsStringExt, process::Command};
+
+    let path: PathEnv = vec![
+        OsString::from("/a b/'q'/$HOME/`x`/\\"),
+        OsString::from_vec(b"/caf\xe9/\xff\xfe".to_vec()),
+        OsString::from("/line\nbreak"),
+    ]
+    .into_iter()
+    .collect();
+
+    for &shell in &[Shell::Sh, Shell::Bash] {
+        let program = match shell_program(shell) {
+            Some(program) => program,
+            None => continue,
+        };
+        let script =
+            format!("{}\nprintf %s \"$PATH\"", shell.export(&path).unwrap());
+        let output = Command::new(program)
+            .arg("-c")
+            .arg(&script)
+            .output()
+            .unwrap();
+        assert_eq!(output.stdout, path.as_os_str().as_bytes(), "{}", script);
+    }
+
+    // PowerShell variable names are case-sensitive outside of Windows.
+    assert_eq!(
+        Shell::PowerShell.export(&PathEnv::from(String::from("/a"))),
+        Ok(String::from("$env:PATH = '/a'"))
+    );
+
+    assert_eq!(
+        Shell::PowerShell.export(&path),
+        Err(shell::ShellError::Unrepresentable(Shell::PowerShell))
+    );
+    assert_eq!(
+        Shell::Cmd.export(&PathEnv::from(String::from("%x%"))),
+        Ok(String::from("set \"PATH=%%x%%\""))
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn shell_add_if_missing_is_idempotent() {
+    use shell::Shell;
+    use std::process::Command;
+
+    let dir = "/opt/it's/bin";
+    let middle = format!("/a{0}{1}{0}/b", SEP, dir);
+
+    for &shell in &[Shell::Sh, Shell::Bash] {
+        let program = match shell_program(shell) {
+            Some(program) => program,
+            None => continue,
+        };
+        let front = shell.prepend_if_missing(dir).unwrap();
+        let back = shell.append_if_missing(dir).unwrap();
+        let run = |path: Option<&str>, snippet: &str| {
+            // Shells set a default `PATH` if none is inherited, so it has to
+            // be unset in the script.
+            let unset = if path.is_none() { "unset PATH\n" } else { "" };
+            let script =
+                format!("{}{1}\n{1}\nprintf %s \"$PATH\"", unset, snippet);
+            let mut command = Command::new(&program);
+            command.arg("-c").arg(&script);
+            if let Some(path) = path {
+                command.env("PATH", path);
+            }
+            command.output().unwrap().stdout
+        };
+
+        let expected = [
+            (Some("/bin"), &front, format!("{}{}/bin", dir, SEP)),
+            (Some("/bin"), &back, format!("/bin{}{}", SEP, dir)),
+            (Some(""), &front, dir.to_owned()),
+            (None, &back, dir.to_owned()),
+            (Some(&middle), &front, middle.clone()),
+        ];
+
+        for (path, snippet, expected) in expected.iter() {
+            let output = run(*path, snippet);
+            assert_eq!(
+                String::from_utf8_lossy(&output),
+                *expected,
+                "{}",
+                snippet
+            );
+        }
+    }
+
+    let pwsh = Shell::PowerShell.prepend_if_missing(dir).unwrap();
+    assert!(pwsh.contains("$env:PATH = "), "{}", pwsh);
+    assert!(!pwsh.contains("$env:Path"), "{}", pwsh);
+
+    assert_eq!(
+        Shell::Cmd.prepend_if_missing(dir),
+        Err(shell::ShellError::Unsupported(Shell::Cmd))
+    );
+}
+
+#[test]
+fn rc_block_install_uninstall() {
+    use shell::{rc::ManagedBlock, Shell};
+
+    let dir = temp_dir("rc");
+    let file = Shell::Fish.rc_file(&dir).unwrap();
+    let block = ManagedBlock::new("path_env test");
+
+    let contents =
+        shell::rc::prepend_contents(Shell::Fish, &["/a", "/b"]).unwrap();
+    assert!(block.install(&file, &contents).unwrap());
+    assert!(!block.install(&file, &contents).unwrap());
+    assert!(block.uninstall(&file).unwrap());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "");
+
+    let original = "alias ll='ls -l'\n";
+    fs::write(&file, original).unwrap();
+
+    assert!(block.install(&file, "one").unwrap());
+    assert!(block.install(&file, "two").unwrap());
+    let text = fs::read_to_string(&file).unwrap();
+    assert_eq!(
+        text,
+        format!(
+            "{}\n{}\ntwo\n{}\n",
+            original,
+            block.start_marker(),
+            block.end_marker()
+        )
+    );
+
+    // Duplicated blocks collapse into one.
+    let doubled = format!("{}{}", text, block.render("three"));
+    assert_eq!(block.update_str(&doubled, Some("two")).unwrap(), text);
+
+    assert!(block.uninstall(&file).unwrap());
+    assert_eq!(fs::read_to_string(&file).unwrap(), original);
+    assert!(!block.uninstall(&file).unwrap());
+
+    let broken = format!("{}\nunterminated\n", block.start_marker());
+    assert!(block.update_str(&broken, None).is_err());
+
+    // Threads writing the same file do not share temporary files.
+    let threads = (0..8)
+        .map(|i| {
+            let file = file.clone();
+            std::thread::spawn(move || {
+                let block = ManagedBlock::new("path_env test");
+                for j in 0..20 {
+                    block.install(&file, &format!("{} {}", i, j)).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert!(block.uninstall(&file).unwrap());
+    assert_eq!(fs::read_to_string(&file).unwrap(), original);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+#[cfg(unix)]
+fn system_environment_files() {
+    let path = system::environment_file(fixture("etc/environment")).unwrap();
+    assert_eq!(path.unwrap().as_os_str(), "/usr/local/bin:/usr/bin:/bin");
+
+    let missing = system::environment_file(fixture("etc/missing")).unwrap();
+    assert!(missing.is_none());
+
+    let dirs = ["user", "etc", "lib", "missing"]
+        .iter()
+        .map(|dir| fixture("environment.d").join(dir))
+        .collect::<Vec<_>>();
+    let base = PathEnv::from(String::from("/usr/bin:/bin"));
+
+    let path = system::environment_d(&dirs, Some(base)).unwrap().unwrap();
+    assert_eq!(
+        path.as_os_str(),
+        "/home/me/bin:/usr/bin:/bin:/opt/extra/bin:/opt/default/bin"
+    );
+
+    let path = system::environment_d(&dirs, None).unwrap().unwrap();
+    assert_eq!(
+        path.as_os_str(),
+        "/home/me/bin::/opt/extra/bin:/opt/default/bin"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn system_paths_files() {
+    let path =
+        system::paths(fixture("etc/paths"), fixture("etc/paths.d")).unwrap();
+    assert_eq!(
+        path.as_os_str(),
+        "/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin\
+         :/Library/Apple/usr/bin:/opt/homebrew/bin"
+    );
+
+    let path =
+        system::paths(fixture("etc/missing"), fixture("etc/paths.d")).unwrap();
+    assert_eq!(
+        path.as_os_str(),
+        "/Library/Apple/usr/bin:/usr/bin:/opt/homebrew/bin"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn system_login_defs() {
+    let defs = system::LoginDefs::read(fixture("etc/login.defs")).unwrap();
+    assert_eq!(
+        defs.path().unwrap().as_os_str(),
+        "/usr/local/bin:/usr/bin:/bin"
+    );
+    assert_eq!(
+        defs.supath().unwrap().as_os_str(),
+        "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin"
+    );
+
+    let defs = system::LoginDefs::read(fixture("etc/missing")).unwrap();
+    assert_eq!(defs, system::LoginDefs::default());
+}
+
+#[test]
+#[cfg(unix)]
+fn getconf_default_path() {
+    let path = PathEnv::from_getconf().unwrap();
+    assert!(
+        path.contains("/bin") || path.contains("/usr/bin"),
+        "{:?}",
+        path
+    );
+
+    let raw = os::unix::getconf().unwrap();
+    assert_eq!(raw.to_str().map(str::trim), raw.to_str());
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn from_pid_matches_child() {
+    let path = PathEnv::from(String::from("/from/pid/a:/from/pid/b"));
+    let sleep = PathEnv::from_var().unwrap().which("sleep").unwrap();
+    let mut child = process::Command::new(sleep)
+        .arg("10")
+        .env("PATH", &path)
+        .spawn()
+        .unwrap();
+
+    // The environment may read as empty until the child finishes `exec`.
+    let mut result = PathEnv::from_pid(child.id());
+    for _ in 0..100 {
+        match result {
+            Ok(None) => {
+                std::thread::sleep(std::time::Duration::from_millis(20))
+            }
+            _ => break,
+        }
+        result = PathEnv::from_pid(child.id());
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(result.unwrap(), Some(path));
+
+    let this = PathEnv::from_pid(process::id()).unwrap();
+    let block = fs::read("/proc/self/environ").unwrap();
+    assert_eq!(this, PathEnv::from_environ(&block));
+}
+
+#[test]
+#[cfg(unix)]
+fn environ_block_parsing() {
+    let env = PathEnv::from_var().unwrap().which("env").unwrap();
+    let output = process::Command::new(env)
+        .arg("-0")
+        .env_clear()
+        .env("A", "x=y")
+        .env("PATH", "/env/zero")
+        .output()
+        .unwrap();
+    let path = PathEnv::from_environ(&output.stdout).unwrap();
+    assert_eq!(path.as_os_str(), "/env/zero");
+
+    let block = b"NOEQUALS\0=C:=C:\\\0PATH=/first\0PATH=/second";
+    let vars = os::unix::environ_vars(block).collect::<Vec<_>>();
+    assert_eq!(vars.len(), 3);
+    assert_eq!(vars[0].0, "=C:");
+    assert_eq!(PathEnv::from_environ(block).unwrap().as_os_str(), "/first");
+    assert!(PathEnv::from_environ(b"").is_none());
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn drift_since_startup() {
+    let process = ProcessPath::lock();
+    let initial = PathDrift::detect().unwrap().initial().cloned();
+
+    let path = std::iter::once(std::path::Path::new("/drift/test/bin"))
+        .chain(initial.iter().flat_map(PathEnv::iter))
+        .collect::<PathEnv>();
+
+    // Restore what other tests may have set, not the startup value.
+    let previous = process.get();
+    process.set(&path);
+
+    let drift = PathDrift::detect().unwrap();
+    match &previous {
+        Some(previous) => process.set(previous),
+        None => process.remove(),
+    }
+
+    assert!(drift.has_drifted());
+    assert_eq!(drift.current(), Some(&path));
+    assert_eq!(drift.diff().to_string(), "+/drift/test/bin at 0");
+    assert_eq!(
+        drift.to_string(),
+        "PATH was modified after startup: +/drift/test/bin at 0"
+    );
+
+    let removed = PathDrift::new(Some(path), None);
+    assert_eq!(removed.to_string(), "PATH was removed after startup");
+}
+
+#[test]
+#[cfg(unix)]
+fn login_shell_path() {
+    let home = fixture("login");
+
+    let mut command = process::Command::new("/bin/sh");
+    command
+        .arg("-l")
+        .arg("-i")
+        .env("HOME", &home)
+        .env("ENV", home.join("env.sh"))
+        .env("PATH", "/initial/bin");
+
+    let path = PathEnv::from_shell_command(command).unwrap();
+    let entries = path.iter().collect::<Vec<_>>();
+    let position = |entry: &str| entries.iter().position(|e| *e == entry);
+
+    // The startup files run in order, on top of what `/etc/profile` sets.
+    let profile = position("/fixture/profile/bin").unwrap();
+    let interactive = position("/fixture/interactive/bin").unwrap();
+    assert_eq!(interactive, 0);
+    assert!(profile > interactive);
+
+    let error = PathEnv::from_login_shell("/nonexistent/shell").unwrap_err();
+    assert!(matches!(error, ShellPathError::Spawn(_)));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_round_trip() {
+    use serde_test::{assert_tokens, Configure, Token};
+
+    let path: PathEnv =
+        vec!["/usr/bin", "/opt/my tools/bin"].into_iter().collect();
+
+    let json = serde_json::to_string(&path).unwrap();
+    assert_eq!(json, r#"["/usr/bin","/opt/my tools/bin"]"#);
+    assert_eq!(serde_json::from_str::<PathEnv>(&json).unwrap(), path);
+
+    let joined = serde_json::to_string(&path.as_os_str().to_str()).unwrap();
+    assert_eq!(serde_json::from_str::<PathEnv>(&joined).unwrap(), path);
+
+    let profile = format!(
+        "joined = '/usr/bin{}/opt/my tools/bin'\n\
+         list = ['/usr/bin', '/opt/my tools/bin']\n",
+        SEP
+    );
+    let profile = profile.parse::<toml::Table>().unwrap();
+    for key in &["joined", "list"] {
+        let value = profile[*key].clone().try_into::<PathEnv>().unwrap();
+        assert_eq!(value, path);
+    }
+
+    assert_tokens(
+        &path.clone().readable(),
+        &[
+            Token::Seq { len: Some(2) },
+            Token::Str("/usr/bin"),
+            Token::Str("/opt/my tools/bin"),
+            Token::SeqEnd,
+        ],
+    );
+
+    #[cfg(unix)]
+    assert_tokens(
+        &path.compact(),
+        &[Token::Bytes(b"/usr/bin:/opt/my tools/bin")],
+    );
+}
+
+#[test]
+#[cfg(all(unix, feature = "serde"))]
+fn serde_non_utf8_is_lossless() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let invalid = std::ffi::OsString::from_vec(b"/opt/\xff/bin".to_vec());
+    let path: PathEnv = vec![std::ffi::OsString::from("/usr/bin"), invalid]
+        .into_iter()
+        .collect();
+
+    let json = serde_json::to_string(&path).unwrap();
+    assert_eq!(
+        json,
+        r#"["/usr/bin",[47,111,112,116,47,255,47,98,105,110]]"#
+    );
+    assert_eq!(serde_json::from_str::<PathEnv>(&json).unwrap(), path);
+
+    serde_test::assert_tokens(
+        &serde_test::Configure::compact(path),
+        &[serde_test::Token::Bytes(b"/usr/bin:/opt/\xff/bin")],
+    );
+}
+
+#[test]
+#[cfg(all(unix, feature = "profiles"))]
+fn profiles_resolve() {
+    use profile::{ProfileError, Profiles};
+
+    let profiles = Profiles::read(fixture("profiles.toml")).unwrap();
+    let lookup = |name: &std::ffi::OsStr| match name.to_str()? {
+        "HOME" => Some("/home/me".into()),
+        "TOOLS" => Some("/opt/tools".into()),
+        _ => None,
+    };
+
+    let path = profiles.resolve_with("release-tools", lookup).unwrap();
+    assert_eq!(
+        path.as_os_str(),
+        "/opt/tools/release/bin:/home/me/.cargo/bin:/opt/tools/bin\
+         :/usr/bin:/bin"
+    );
+
+    match profiles.resolve_with("loop-a", lookup) {
+        Err(ProfileError::Cycle(cycle)) => {
+            assert_eq!(cycle, ["loop-b", "loop-c", "loop-b"]);
+        }
+        result => panic!("{:?}", result),
+    }
+    match profiles.resolve_with("broken", lookup) {
+        Err(ProfileError::NotFound(name)) => assert_eq!(name, "missing"),
+        result => panic!("{:?}", result),
+    }
+
+    let error = Profiles::parse("[build]\nentry = []\n").unwrap_err();
+    assert!(matches!(error, ProfileError::Parse(_)));
+}
+
+#[test]
+#[cfg(unix)]
+fn audit_reports_risky_entries() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir("audit");
+    let mode_dir = |name: &str, mode: u32| {
+        let path = dir.join(name);
+        fs::create_dir(&path).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        path
+    };
+    let safe = mode_dir("safe", 0o755);
+    let world = mode_dir("world", 0o777);
+    let sticky = mode_dir("sticky", 0o1777);
+    let group = mode_dir("group", 0o775);
+
+    let path = PathEnv::from(format!(
+        "/usr/bin::.:bin:{}:{}:{}:{}",
+        safe.display(),
+        world.display(),
+        sticky.display(),
+        group.display()
+    ));
+    let audit = path.audit();
+    // The temporary directory may or may not be under `/tmp`, which is
+    // checked separately below.
+    let findings = audit
+        .iter()
+        .filter(|finding| finding.issue() != AuditIssue::Temporary)
+        .map(|finding| (finding.index(), finding.issue()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        findings,
+        [
+            (1, AuditIssue::Empty),
+            (2, AuditIssue::Relative),
+            (3, AuditIssue::Relative),
+            (5, AuditIssue::WorldWritable),
+            (7, AuditIssue::GroupWritable),
+        ]
+    );
+    assert_eq!(audit.max_severity(), Some(Severity::High));
+
+    let world_writable = audit
+        .iter()
+        .find(|finding| finding.issue() == AuditIssue::WorldWritable);
+    assert_eq!(
+        world_writable.unwrap().to_string(),
+        format!(
+            "high: entry 5 ({}) is writable by all users",
+            world.display()
+        )
+    );
+    assert_eq!(
+        audit.iter().next().unwrap().to_string(),
+        "high: entry 1 is empty"
+    );
+
+    assert!(PathEnv::from("/usr/bin".to_owned()).audit().is_empty());
+
+    // Temporary directories are matched by name, whether or not they exist.
+    let path = PathEnv::from(
+        "/tmp/path_env-audit/bin:/var/tmp:/tmpfs/bin:/usr/bin".to_owned(),
+    );
+    let temporary = path
+        .audit()
+        .iter()
+        .filter(|finding| finding.issue() == AuditIssue::Temporary)
+        .map(|finding| (finding.index(), finding.severity()))
+        .collect::<Vec<_>>();
+    assert_eq!(temporary, [(0, Severity::Medium), (1, Severity::Medium)]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn prune_reports_reasons() {
+    let dir = temp_dir("prune");
+    let bin = dir.join("bin");
+    let link = dir.join("link");
+    let broken = dir.join("broken");
+    let file = dir.join("file");
+    let missing = dir.join("missing");
+    fs::create_dir(&bin).unwrap();
+    std::os::unix::fs::symlink(&bin, &link).unwrap();
+    std::os::unix::fs::symlink(&missing, &broken).unwrap();
+    fs::write(&file, "").unwrap();
+
+    let entries = [&missing, &bin, &file, &link, &broken];
+    let original: PathEnv = entries
+        .iter()
+        .map(|entry| entry.as_path())
+        .chain(Some(std::path::Path::new("relative/bin")))
+        .collect();
+
+    let mut path = original.clone();
+    let report = path.prune(PrunePolicy::new());
+    let removed = report
+        .iter()
+        .map(|entry| (entry.index(), entry.path(), entry.reason()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        removed,
+        [
+            (0, missing.as_path(), PruneReason::Missing),
+            (2, file.as_path(), PruneReason::NotDir),
+            (4, broken.as_path(), PruneReason::Missing),
+        ]
+    );
+    assert_eq!(
+        path.iter().collect::<Vec<_>>(),
+        [bin.as_path(), link.as_path(), "relative/bin".as_ref()]
+    );
+
+    let mut path = original.clone();
+    let report = path.prune(PrunePolicy::new().with_missing(false));
+    assert_eq!(
+        report.to_string(),
+        format!("-{} (is not a directory)", file.display())
+    );
+    assert_eq!(path.len(), original.len() - 1);
+
+    // Empty entries mean the current directory, so they are kept and
+    // counted in indices.
+    let value =
+        format!("{0}{1}{0}{0}{2}{0}", SEP, bin.display(), missing.display());
+    let mut path = PathEnv::from(value);
+    let report = path.prune(PrunePolicy::new());
+    assert_eq!(report.iter().next().unwrap().index(), 3);
+    assert_eq!(report.len(), 1);
+    assert_eq!(
+        path.as_os_str(),
+        &*format!("{0}{1}{0}{0}", SEP, bin.display())
+    );
+
+    // A lone empty entry is still there for `audit` to report.
+    let mut path = PathEnv::from(format!("{}{}", missing.display(), SEP));
+    assert_eq!(path.prune(PrunePolicy::new()).len(), 1);
+    assert_eq!(path.as_os_str(), SEP);
+    assert!(path
+        .audit()
+        .iter()
+        .all(|finding| finding.issue() == AuditIssue::Empty));
+    assert!(!path.audit().is_empty());
+
+    let value = format!("{}::{}", bin.display(), SEP);
+    let mut path = PathEnv::from(value.clone());
+    assert!(path.prune(PrunePolicy::new()).is_empty());
+    assert_eq!(path.as_os_str(), &*value);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,358 @@
+//! List-valued environment variables other than `PATH`.
+//!
+//! Many environment variables share the format of `PATH`, but differ in what
+//! an empty entry means. A [`ListVar`] describes one such variable, and can be
+//! used with [`PathEnv::from_var_named`] and [`PathEnv::set_var_named`].
+//!
+//! # Examples
+//!
+//! ```
+//! use path_env::{vars, PathEnv};
+//!
+//! let mut lib_path = PathEnv::from_var_named(vars::LD_LIBRARY_PATH)
+//!     .unwrap_or_else(PathEnv::empty);
+//!
+//! lib_path.push_back("/opt/mytool/lib");
+//! # let _ = lib_path;
+//! ```
+//!
+//! [`ListVar`]:                 struct.ListVar.html
+//! [`PathEnv::from_var_named`]: ../struct.PathEnv.html#method.from_var_named
+//! [`PathEnv::set_var_named`]:  ../struct.PathEnv.html#method.set_var_named
+
+use crate::{lock, separator, sys::byte_repr::ByteRepr, PathEnv};
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    path::Path,
+};
+
+/// What an empty entry in a list variable stands for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EmptyEntry {
+    /// Empty entries carry no meaning and are dropped.
+    Ignored,
+    /// Empty entries refer to the current directory and are read as `.`.
+    CurrentDir,
+    /// Empty entries mark where the variable's default list goes, as in
+    /// `MANPATH`.
+    ///
+    /// These are dropped when reading the variable into a [`PathEnv`].
+    ///
+    /// [`PathEnv`]: ../struct.PathEnv.html
+    Default,
+}
+
+/// Describes a list-valued environment variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ListVar<'a> {
+    name: &'a str,
+    separator: u8,
+    empty: EmptyEntry,
+}
+
+impl<'a> ListVar<'a> {
+    /// Describes the variable `name`, which uses the `PATH` separator and
+    /// ignores empty entries.
+    #[inline]
+    pub const fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            separator: separator::U8,
+            empty: EmptyEntry::Ignored,
+        }
+    }
+
+    /// Returns `self` with entries separated by `separator` instead.
+    ///
+    /// Entries that contain the `PATH` separator cannot be represented by a
+    /// [`PathEnv`] and are split apart when read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `separator` is not an ASCII byte, since entries could then
+    /// be split in the middle of a character.
+    ///
+    /// [`PathEnv`]: ../struct.PathEnv.html
+    #[inline]
+    pub const fn with_separator(mut self, separator: u8) -> Self {
+        assert!(separator.is_ascii(), "separator must be ASCII");
+        self.separator = separator;
+        self
+    }
+
+    /// Returns `self` with empty entries interpreted as `empty`.
+    #[inline]
+    pub const fn with_empty(mut self, empty: EmptyEntry) -> Self {
+        self.empty = empty;
+        self
+    }
+
+    /// Returns the name of the variable.
+    #[inline]
+    pub const fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns the byte that separates entries in the variable.
+    #[inline]
+    pub const fn separator(&self) -> u8 {
+        self.separator
+    }
+
+    /// Returns what an empty entry in the variable stands for.
+    #[inline]
+    pub const fn empty(&self) -> EmptyEntry {
+        self.empty
+    }
+
+    /// Returns an iterator over the raw entries of `value`, including empty
+    /// ones.
+    ///
+    /// Unlike [`split`], empty entries are kept so that markers such as those
+    /// in `MANPATH` can be seen. An empty `value` has no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// let value = format!("{0}/opt/man{0}", path_env::separator::STR);
+    /// let entries = path_env::vars::MANPATH
+    ///     .entries(value.as_ref())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(entries, ["".as_ref(), Path::new("/opt/man"), "".as_ref()]);
+    /// ```
+    ///
+    /// [`split`]: ../fn.split.html
+    pub fn entries(self, value: &OsStr) -> impl Iterator<Item = &Path> {
+        let sep = self.separator;
+        let bytes = Some(value.as_bytes()).filter(|bytes| !bytes.is_empty());
+
+        // SAFETY: `sep` is ASCII and thus always a boundary in the platform's
+        // `OsStr` encoding.
+        bytes
+            .into_iter()
+            .flat_map(move |bytes| bytes.split(move |&b| b == sep))
+            .map(|entry| unsafe { Path::from_bytes(entry) })
+    }
+
+    /// Parses `value` as the contents of this variable.
+    pub(crate) fn parse(self, value: &OsStr) -> PathEnv {
+        let empty = self.empty;
+        self.entries(value)
+            .filter_map(|entry| {
+                if !entry.as_os_str().is_empty() {
+                    Some(entry)
+                } else if empty == EmptyEntry::CurrentDir {
+                    Some(Path::new("."))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the effective list for `value`, the contents of this variable,
+    /// given the list that applies when the variable is not set.
+    ///
+    /// If `value` is `None` or empty, the result is `default`. Otherwise, each
+    /// empty entry is handled according to [`empty`], where
+    /// [`EmptyEntry::Default`] markers are replaced by `default` in place.
+    ///
+    /// # Examples
+    ///
+    /// A leading separator in `MANPATH` puts the default search path first:
+    ///
+    /// ```
+    /// use path_env::{vars, PathEnv};
+    ///
+    /// let sep = path_env::separator::STR;
+    /// let default: PathEnv = vec!["/usr/share/man"].into_iter().collect();
+    ///
+    /// let value = format!("{}/opt/man", sep);
+    /// let man_path = vars::MANPATH.resolve(Some(value.as_ref()), &default);
+    ///
+    /// assert_eq!(man_path, *format!("/usr/share/man{}/opt/man", sep));
+    /// assert_eq!(vars::MANPATH.resolve(None, &default), default);
+    /// ```
+    ///
+    /// [`empty`]:               #method.empty
+    /// [`EmptyEntry::Default`]: enum.EmptyEntry.html#variant.Default
+    pub fn resolve(self, value: Option<&OsStr>, default: &PathEnv) -> PathEnv {
+        let value = match value {
+            Some(value) if !value.is_empty() => value,
+            _ => return default.clone(),
+        };
+
+        if self.empty != EmptyEntry::Default {
+            return self.parse(value);
+        }
+
+        let mut entries = Vec::new();
+        for entry in self.entries(value) {
+            if entry.as_os_str().is_empty() {
+                entries.extend(default.iter());
+            } else {
+                entries.push(entry);
+            }
+        }
+        entries.into_iter().collect()
+    }
+
+    /// Joins `path` using the separator of this variable.
+    pub(crate) fn join(self, path: &PathEnv) -> OsString {
+        if self.separator == separator::U8 {
+            return path.as_os_str().to_owned();
+        }
+
+        let sep = [self.separator];
+        // SAFETY: `sep` is ASCII.
+        let sep = unsafe { OsStr::from_bytes(&sep) };
+
+        let mut result = OsString::with_capacity(path.as_os_str().len());
+        for (i, part) in path.iter().enumerate() {
+            if i != 0 {
+                result.push(sep);
+            }
+            result.push(part);
+        }
+        result
+    }
+}
+
+impl<'a> From<&'a str> for ListVar<'a> {
+    /// Returns the known variable called `name`, or [`ListVar::new`] if there
+    /// is none.
+    ///
+    /// [`ListVar::new`]: struct.ListVar.html#method.new
+    fn from(name: &'a str) -> Self {
+        KNOWN
+            .iter()
+            .find(|var| var.name == name)
+            .map(|var| Self { name, ..*var })
+            .unwrap_or_else(|| Self::new(name))
+    }
+}
+
+#[cfg(windows)]
+const PATH_EMPTY: EmptyEntry = EmptyEntry::Ignored;
+
+#[cfg(not(windows))]
+const PATH_EMPTY: EmptyEntry = EmptyEntry::CurrentDir;
+
+/// The `PATH` variable.
+///
+/// Empty entries refer to the current directory on Unix-like systems and are
+/// ignored on Windows.
+pub const PATH: ListVar<'static> = ListVar::new("PATH").with_empty(PATH_EMPTY);
+
+/// The dynamic linker's `LD_LIBRARY_PATH` variable.
+pub const LD_LIBRARY_PATH: ListVar<'static> =
+    ListVar::new("LD_LIBRARY_PATH").with_empty(EmptyEntry::CurrentDir);
+
+/// The `PKG_CONFIG_PATH` variable used by `pkg-config`.
+pub const PKG_CONFIG_PATH: ListVar<'static> = ListVar::new("PKG_CONFIG_PATH");
+
+/// The `PYTHONPATH` variable used by Python.
+pub const PYTHONPATH: ListVar<'static> =
+    ListVar::new("PYTHONPATH").with_empty(EmptyEntry::CurrentDir);
+
+/// The `MANPATH` variable used by `man`.
+///
+/// Empty entries stand for the system's default search path.
+pub const MANPATH: ListVar<'static> =
+    ListVar::new("MANPATH").with_empty(EmptyEntry::Default);
+
+/// The `XDG_DATA_DIRS` variable from the XDG Base Directory Specification.
+pub const XDG_DATA_DIRS: ListVar<'static> = ListVar::new("XDG_DATA_DIRS");
+
+/// The `XDG_CONFIG_DIRS` variable from the XDG Base Directory Specification.
+pub const XDG_CONFIG_DIRS: ListVar<'static> = ListVar::new("XDG_CONFIG_DIRS");
+
+/// The `CLASSPATH` variable used by Java.
+pub const CLASSPATH: ListVar<'static> =
+    ListVar::new("CLASSPATH").with_empty(EmptyEntry::CurrentDir);
+
+/// The value of [`XDG_DATA_DIRS`] to use when it is not set or empty.
+///
+/// [`XDG_DATA_DIRS`]: constant.XDG_DATA_DIRS.html
+pub const XDG_DATA_DIRS_DEFAULT: &[&str] =
+    &["/usr/local/share/", "/usr/share/"];
+
+/// The value of [`XDG_CONFIG_DIRS`] to use when it is not set or empty.
+///
+/// [`XDG_CONFIG_DIRS`]: constant.XDG_CONFIG_DIRS.html
+pub const XDG_CONFIG_DIRS_DEFAULT: &[&str] = &["/etc/xdg"];
+
+/// Variables that `ListVar::from` recognizes by name.
+const KNOWN: &[ListVar<'static>] = &[
+    PATH,
+    LD_LIBRARY_PATH,
+    PKG_CONFIG_PATH,
+    PYTHONPATH,
+    MANPATH,
+    XDG_DATA_DIRS,
+    XDG_CONFIG_DIRS,
+    CLASSPATH,
+];
+
+impl PathEnv {
+    /// Creates an instance by fetching the environment variable described by
+    /// `var`.
+    ///
+    /// Empty entries are handled according to [`ListVar::empty`].
+    ///
+    /// # Examples
+    ///
+    /// Known variables can be referred to by name:
+    ///
+    /// ```
+    /// let python_path = path_env::PathEnv::from_var_named("PYTHONPATH");
+    /// # let _ = python_path;
+    /// ```
+    ///
+    /// [`ListVar::empty`]: vars/struct.ListVar.html#method.empty
+    pub fn from_var_named<'a, V: Into<ListVar<'a>>>(var: V) -> Option<Self> {
+        let var = var.into();
+        lock::var_os(var.name).map(|value| var.parse(&value))
+    }
+
+    /// Creates an instance with the effective contents of the environment
+    /// variable described by `var`, given the list that applies when the
+    /// variable is not set.
+    ///
+    /// See [`ListVar::resolve`] for more info.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use path_env::{vars, PathEnv};
+    ///
+    /// let default = vars::XDG_DATA_DIRS_DEFAULT.iter().collect();
+    /// let data_dirs = PathEnv::from_var_resolved(vars::XDG_DATA_DIRS, &default);
+    /// # let _ = data_dirs;
+    /// ```
+    ///
+    /// [`ListVar::resolve`]: vars/struct.ListVar.html#method.resolve
+    pub fn from_var_resolved<'a, V: Into<ListVar<'a>>>(
+        var: V,
+        default: &PathEnv,
+    ) -> Self {
+        let var = var.into();
+        var.resolve(lock::var_os(var.name).as_deref(), default)
+    }
+
+    /// Sets the environment variable described by `var` to `self` for the
+    /// currently running process.
+    ///
+    /// See [`env::set_var`] for more info.
+    ///
+    /// [`env::set_var`]: https://doc.rust-lang.org/std/env/fn.set_var.html
+    pub fn set_var_named<'a, V: Into<ListVar<'a>>>(&self, var: V) {
+        let var = var.into();
+        let _lock = lock::ENV.lock();
+        env::set_var(var.name, var.join(self));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::PathEnv;
+use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    path::{self, Path, PathBuf},
+};
+
+/// Returns whether `program` names a file directly instead of being looked up.
+fn has_separator(program: &OsStr) -> bool {
+    program.to_string_lossy().chars().any(path::is_separator)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    match fs::metadata(path) {
+        Ok(meta) => meta.is_file() && meta.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|meta| meta.is_file())
+        .unwrap_or(false)
+}
+
+/// Returns the file names to try for `program` in each directory.
+#[cfg(windows)]
+fn candidates(program: &OsStr) -> Vec<OsString> {
+    if Path::new(program).extension().is_some() {
+        return vec![program.to_owned()];
+    }
+
+    let exts = crate::lock::var_os("PATHEXT")
+        .unwrap_or_else(|| ".COM;.EXE;.BAT;.CMD".into());
+
+    crate::split(&exts)
+        .map(|ext| {
+            let mut name = program.to_owned();
+            name.push(ext);
+            name
+        })
+        .collect()
+}
+
+/// Returns the file names to try for `program` in each directory.
+#[cfg(not(windows))]
+fn candidates(program: &OsStr) -> Vec<OsString> {
+    vec![program.to_owned()]
+}
+
+impl PathEnv {
+    /// Returns the first executable named `program` found in `self`.
+    ///
+    /// This follows the lookup a shell would perform with `self` as its
+    /// `PATH`. If `program` contains a path separator, it is returned as-is
+    /// when it refers to an executable. On Windows, names without an
+    /// extension are tried with each extension in `PATHEXT`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn example() -> Option<()> {
+    /// let path = path_env::var()?;
+    ///
+    /// if let Some(cargo) = path.which("cargo") {
+    ///     println!("cargo is at {}", cargo.display());
+    /// }
+    /// # Some(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn which<P: AsRef<OsStr>>(&self, program: P) -> Option<PathBuf> {
+        self.which_all(program).next()
+    }
+
+    /// Returns an iterator over every executable named `program` in `self`,
+    /// in lookup order.
+    ///
+    /// See [`which`](#method.which) for how `program` is interpreted.
+    pub fn which_all<P: AsRef<OsStr>>(
+        &self,
+        program: P,
+    ) -> impl Iterator<Item = PathBuf> + '_ {
+        let program = program.as_ref();
+
+        let (direct, names) = if program.is_empty() {
+            (None, Vec::new())
+        } else if has_separator(program) {
+            (Some(PathBuf::from(program)), Vec::new())
+        } else {
+            (None, candidates(program))
+        };
+
+        let direct = direct.filter(|path| is_executable(path));
+        let found = self.iter().flat_map(move |dir| {
+            names
+                .iter()
+                .map(move |name| dir.join(name))
+                .filter(|path| is_executable(path))
+                .collect::<Vec<_>>()
+        });
+
+        direct.into_iter().chain(found)
+    }
+}
@@ -0,0 +1,143 @@
+#![cfg(all(unix, feature = "cli"))]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+const PATH: &str = "/usr/bin:/opt/a/bin:/bin:/usr/bin";
+
+/// Runs `path-env` with `PATH` set to `PATH`.
+fn path_env(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_path-env"))
+        .args(args)
+        .env("PATH", PATH)
+        .output()
+        .unwrap()
+}
+
+fn stdout(args: &[&str]) -> String {
+    let output = path_env(args);
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn list() {
+    assert_eq!(
+        stdout(&["list"]),
+        "0  /usr/bin\n1  /opt/a/bin\n2  /bin\n3  /usr/bin\n"
+    );
+}
+
+#[test]
+fn edit() {
+    assert_eq!(
+        stdout(&["add", "/x", "/y"]),
+        "/x:/y:/usr/bin:/opt/a/bin:/bin:/usr/bin\n"
+    );
+    assert_eq!(
+        stdout(&["add", "--back", "/usr/bin"]),
+        "/opt/a/bin:/bin:/usr/bin\n"
+    );
+    assert_eq!(stdout(&["remove", "/usr/bin"]), "/opt/a/bin:/bin\n");
+    assert_eq!(stdout(&["dedup"]), "/usr/bin:/opt/a/bin:/bin\n");
+}
+
+#[test]
+fn diff_and_export() {
+    assert_eq!(
+        stdout(&["diff", "/usr/bin:/bin:/usr/bin"]),
+        "+/opt/a/bin at 1\n"
+    );
+    assert_eq!(
+        stdout(&["export", "--shell", "fish"]),
+        "set -gx PATH '/usr/bin' '/opt/a/bin' '/bin' '/usr/bin'\n"
+    );
+}
+
+#[test]
+fn which() {
+    let output = path_env(&["which", "sh"]);
+    assert!(output.status.success());
+    assert!(output.stdout.ends_with(b"/sh\n"));
+
+    let output = path_env(&["which", "path-env-missing-program"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_path-env"))
+        .arg("--stdin")
+        .arg("list")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"/a:/b\n").unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.stdout, b"0  /a\n1  /b\n");
+}
+
+#[test]
+fn usage_errors() {
+    let cases: &[&[&str]] = &[
+        &[],
+        &["frobnicate"],
+        &["list", "extra"],
+        &["add"],
+        &["add", "--front", "--back", "/c"],
+        &["add", "--back", "--front", "/c"],
+    ];
+    for args in cases {
+        let output = path_env(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(!output.stderr.is_empty());
+    }
+}
+
+#[test]
+fn exec() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let dir = std::env::temp_dir()
+        .join(format!("path_env-cli-{}-exec", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let tool = dir.join("tool");
+    fs::write(
+        &tool,
+        "#!/bin/sh\nprintf '%s\\n' \"$PATH\" \"$0\" \"$*\"\nexit 3\n",
+    )
+    .unwrap();
+    fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let dir = dir.to_str().unwrap();
+    let output = path_env(&[
+        "exec",
+        "--prepend",
+        dir,
+        "--remove",
+        "/opt/a/bin",
+        "--dedup",
+        "--",
+        "tool",
+        "x",
+        "y",
+    ]);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("{}:/usr/bin:/bin\n{}/tool\nx y\n", dir, dir)
+    );
+
+    // Found in the edited PATH only.
+    let output = path_env(&["exec", "tool"]);
+    assert_eq!(output.status.code(), Some(127));
+
+    fs::remove_dir_all(dir).unwrap();
+}
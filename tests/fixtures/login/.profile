@@ -0,0 +1,5 @@
+# Login startup file that prints noise, as many real ones do.
+echo "Welcome back!"
+printf 'no trailing newline'
+PATH="/fixture/profile/bin:$PATH"
+export PATH
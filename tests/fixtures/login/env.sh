@@ -0,0 +1,3 @@
+# Interactive startup file, read through `ENV`.
+echo "PATH=/not/the/real/path"
+PATH="/fixture/interactive/bin:$PATH"